@@ -0,0 +1,144 @@
+pub mod baseline {
+    use serde::{Serialize, Deserialize};
+    use std::collections::HashMap;
+    use std::fs;
+    use std::path::Path;
+
+    const BASELINE_VERSION: u32 = 1;
+
+    /// A finding identified by where it is rather than by its line numbers.
+    #[derive(Serialize, Deserialize, Debug, Clone)]
+    pub struct BaselineFinding {
+        /// Path relative to the working directory
+        pub file: String,
+        /// Qualified name of the function
+        pub function: String,
+        pub body_hash: String,
+        pub score: usize,
+    }
+
+    /// Findings known at some point, only new or worsened findings are reported against it.
+    #[derive(Serialize, Deserialize, Debug)]
+    pub struct Baseline {
+        pub version: u32,
+        /// Normalization model the scores were computed with, like `threshold-relative@1`
+        #[serde(default)]
+        pub normalization: Option<String>,
+        pub findings: Vec<BaselineFinding>,
+    }
+
+    impl Baseline {
+        pub fn new(findings: Vec<BaselineFinding>, normalization: String) -> Self {
+            Baseline { version: BASELINE_VERSION, normalization: Some(normalization), findings }
+        }
+
+        pub fn load(path: &Path) -> Result<Self, String> {
+            let data = fs::read_to_string(path).map_err(|e| e.to_string())?;
+            let baseline: Baseline = serde_json::from_str(&data).map_err(|e| e.to_string())?;
+            if baseline.version != BASELINE_VERSION {
+                return Err(format!("unsupported baseline version {}", baseline.version));
+            }
+            Ok(baseline)
+        }
+
+        pub fn store(&self, path: &Path) -> std::io::Result<()> {
+            let serialized = serde_json::to_string_pretty(self)?;
+            fs::write(path, serialized)
+        }
+
+        /// Whether each finding is new or scores higher than its baseline counterpart, scores are
+        /// only compared when `compare_scores` is set. Findings match by body hash first, then by name
+        /// so that edited functions are still recognized.
+        pub fn new_or_worsened(&self, findings: &[BaselineFinding], compare_scores: bool) -> Vec<bool> {
+            let mut by_hash: HashMap<(&str, &str, &str), Vec<usize>> = HashMap::new();
+            let mut by_name: HashMap<(&str, &str), Vec<usize>> = HashMap::new();
+            for known in &self.findings {
+                by_hash.entry((&known.file, &known.function, &known.body_hash)).or_default().push(known.score);
+            }
+
+            let mut matched: Vec<Option<usize>> = findings.iter()
+                .map(|finding| {
+                    by_hash.get_mut(&(finding.file.as_str(), finding.function.as_str(), finding.body_hash.as_str()))
+                        .and_then(Vec::pop)
+                })
+                .collect();
+
+            // Known findings whose body changed are left over for the name lookup
+            for ((file, function, _), scores) in &by_hash {
+                by_name.entry((*file, *function)).or_default().extend(scores);
+            }
+            for (finding, known_score) in findings.iter().zip(matched.iter_mut()) {
+                if known_score.is_none() {
+                    *known_score = by_name.get_mut(&(finding.file.as_str(), finding.function.as_str())).and_then(Vec::pop);
+                }
+            }
+
+            findings.iter()
+                .zip(matched)
+                .map(|(finding, known_score)| known_score.is_none_or(|known_score| compare_scores && finding.score > known_score))
+                .collect()
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::{Baseline, BaselineFinding};
+
+        fn finding(function: &str, body_hash: &str, score: usize) -> BaselineFinding {
+            BaselineFinding { file: "src/math.ts".to_string(), function: function.to_string(), body_hash: body_hash.to_string(), score }
+        }
+
+        fn baseline(findings: Vec<BaselineFinding>) -> Baseline {
+            Baseline::new(findings, "threshold-relative@1".to_string())
+        }
+
+        #[test]
+        fn unknown_findings_are_new() {
+            let known = baseline(vec![finding("mix", "1", 5)]);
+            let current = [finding("blur", "2", 5), BaselineFinding { file: "src/other.ts".to_string(), ..finding("mix", "1", 5) }];
+            assert_eq!(known.new_or_worsened(&current, true), vec![true, true]);
+        }
+
+        #[test]
+        fn edited_function_matches_by_name() {
+            let known = baseline(vec![finding("mix", "1", 5)]);
+            assert_eq!(known.new_or_worsened(&[finding("mix", "2", 5)], true), vec![false]);
+        }
+
+        #[test]
+        fn hash_match_wins_over_name_match() {
+            // Two functions with the same name, only the edited one scores higher than its own entry
+            let known = baseline(vec![finding("mix", "1", 3), finding("mix", "2", 8)]);
+            let current = [finding("mix", "3", 6), finding("mix", "2", 8)];
+            assert_eq!(known.new_or_worsened(&current, true), vec![true, false]);
+        }
+
+        #[test]
+        fn each_known_finding_matches_once() {
+            let known = baseline(vec![finding("mix", "1", 5)]);
+            assert_eq!(known.new_or_worsened(&[finding("mix", "1", 5), finding("mix", "1", 5)], true), vec![false, true]);
+        }
+
+        #[test]
+        fn higher_score_is_worsened() {
+            let known = baseline(vec![finding("mix", "1", 5)]);
+            assert_eq!(known.new_or_worsened(&[finding("mix", "1", 6)], true), vec![true]);
+            assert_eq!(known.new_or_worsened(&[finding("mix", "1", 4)], true), vec![false]);
+        }
+
+        #[test]
+        fn scores_are_not_compared_across_normalizations() {
+            let known = baseline(vec![finding("mix", "1", 5)]);
+            let current = [finding("mix", "1", 9), finding("blur", "2", 1)];
+            assert_eq!(known.new_or_worsened(&current, false), vec![false, true]);
+        }
+
+        #[test]
+        fn baseline_without_normalization_still_loads() {
+            let json = r#"{ "version": 1, "findings": [{ "file": "a.js", "function": "f", "body_hash": "1", "score": 4 }] }"#;
+            let baseline: Baseline = serde_json::from_str(json).expect("Failed to parse baseline");
+            assert_eq!(baseline.normalization, None);
+            assert_eq!(baseline.findings[0].score, 4);
+        }
+    }
+}
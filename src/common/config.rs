@@ -0,0 +1,184 @@
+pub mod config {
+    use serde::{Serialize, Deserialize};
+    use std::collections::BTreeMap;
+    use std::fmt;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use crate::common::Metrics;
+    use crate::common::config_dir::config_dir::config_directory;
+
+    const USER_CONFIG_FILE: &str = "config.json";
+    pub const PROJECT_CONFIG_FILE: &str = ".wasm-grate.json";
+
+    /// Where a threshold value comes from, later layers override earlier ones:
+    /// defaults, the user config, the project config and finally command line flags.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+    pub enum ConfigLayer {
+        Default,
+        User,
+        Project,
+    }
+
+    impl fmt::Display for ConfigLayer {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match self {
+                ConfigLayer::Default => write!(f, "default"),
+                ConfigLayer::User => write!(f, "user"),
+                ConfigLayer::Project => write!(f, "project"),
+            }
+        }
+    }
+
+    /// Contents of a user or project configuration file, every key is optional.
+    #[derive(Serialize, Deserialize, Debug, Default)]
+    pub struct ConfigFile {
+        #[serde(default)]
+        pub thresholds: BTreeMap<String, usize>,
+    }
+
+    impl ConfigFile {
+        /// An empty configuration when the file does not exist.
+        pub fn load(path: &Path) -> Result<Self, String> {
+            if !path.exists() {
+                return Ok(ConfigFile::default());
+            }
+            let data = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+            let config: ConfigFile = serde_json::from_str(&data).map_err(|e| format!("{}: {}", path.display(), e))?;
+            let mut validated = Metrics::new();
+            for (key, &value) in &config.thresholds {
+                validated.set(key, value).map_err(|e| format!("{}: {}", path.display(), e))?;
+            }
+            Ok(config)
+        }
+
+        /// Writes the file, or removes it once nothing is left in it.
+        pub fn store(&self, path: &Path) -> std::io::Result<()> {
+            if self.thresholds.is_empty() {
+                if path.exists() {
+                    fs::remove_file(path)?;
+                }
+                return Ok(());
+            }
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(path, serde_json::to_string_pretty(self)?)
+        }
+    }
+
+    /// Thresholds after applying every configuration layer.
+    pub struct ResolvedConfig {
+        pub thresholds: Metrics,
+        sources: BTreeMap<String, ConfigLayer>,
+    }
+
+    impl ResolvedConfig {
+        pub fn source(&self, key: &str) -> ConfigLayer {
+            self.sources.get(key).copied().unwrap_or(ConfigLayer::Default)
+        }
+    }
+
+    pub fn user_config_path() -> Option<PathBuf> {
+        config_directory().ok().map(|config_dir| config_dir.join(USER_CONFIG_FILE))
+    }
+
+    /// Root of the git repository containing `start`, or `start` itself (its directory for a file)
+    /// outside of a repository.
+    pub fn project_root(start: &Path) -> PathBuf {
+        let start = fs::canonicalize(start).unwrap_or(start.to_path_buf());
+        let start = if start.is_file() { start.parent().map(Path::to_path_buf).unwrap_or(start) } else { start };
+        start.ancestors()
+            .find(|dir| dir.join(".git").exists())
+            .unwrap_or(&start)
+            .to_path_buf()
+    }
+
+    /// `.wasm-grate.json` at the project root.
+    pub fn project_config_path(start: &Path) -> PathBuf {
+        project_root(start).join(PROJECT_CONFIG_FILE)
+    }
+
+    pub fn config_path(layer: ConfigLayer, project_start: &Path) -> Option<PathBuf> {
+        match layer {
+            ConfigLayer::Default => None,
+            ConfigLayer::User => user_config_path(),
+            ConfigLayer::Project => Some(project_config_path(project_start)),
+        }
+    }
+
+    pub fn resolve_config(project_start: &Path) -> Result<ResolvedConfig, String> {
+        let mut resolved = ResolvedConfig { thresholds: Metrics::new(), sources: BTreeMap::new() };
+        for layer in [ConfigLayer::User, ConfigLayer::Project] {
+            let Some(path) = config_path(layer, project_start) else {
+                continue;
+            };
+            for (key, value) in ConfigFile::load(&path)?.thresholds {
+                resolved.thresholds.set(&key, value)?;
+                resolved.sources.insert(key, layer);
+            }
+        }
+        Ok(resolved)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::fs;
+        use crate::fixtures::fixtures::project;
+        use super::{project_config_path, project_root, ConfigFile, PROJECT_CONFIG_FILE};
+
+        #[test]
+        fn missing_file_is_an_empty_config() {
+            let root = project("config-missing", &[]);
+            let config = ConfigFile::load(&root.join(PROJECT_CONFIG_FILE)).expect("Missing config must load");
+            assert!(config.thresholds.is_empty());
+            let _ = fs::remove_dir_all(root);
+        }
+
+        #[test]
+        fn stored_thresholds_load_back_and_an_empty_config_removes_the_file() {
+            let root = project("config-round-trip", &[]);
+            let path = root.join("nested/config.json");
+            let mut config = ConfigFile::default();
+            config.thresholds.insert("loop_depth".to_string(), 4);
+            config.store(&path).expect("Failed to store config");
+            assert_eq!(ConfigFile::load(&path).unwrap().thresholds.get("loop_depth"), Some(&4));
+
+            config.thresholds.clear();
+            config.store(&path).expect("Failed to store config");
+            assert!(!path.exists());
+            let _ = fs::remove_dir_all(root);
+        }
+
+        #[test]
+        fn unknown_keys_and_invalid_values_are_rejected() {
+            for content in [
+                r#"{ "thresholds": { "nesting": 3 } }"#,
+                r#"{ "thresholds": { "loop_depth": -1 } }"#,
+                r#"{ "thresholds": { "loop_depth": "2" } }"#,
+                r#"{ "thresholds": [] }"#,
+                "not json",
+            ] {
+                let root = project("config-invalid", &[(PROJECT_CONFIG_FILE, content)]);
+                let error = ConfigFile::load(&root.join(PROJECT_CONFIG_FILE)).expect_err(content);
+                assert!(error.contains(PROJECT_CONFIG_FILE), "case {:?}: {}", content, error);
+                let _ = fs::remove_dir_all(root);
+            }
+        }
+
+        #[test]
+        fn project_config_sits_at_the_repository_root() {
+            let root = project("config-root", &[(".git/HEAD", ""), ("src/lib/math.js", "")]);
+            assert_eq!(project_root(&root.join("src/lib/math.js")), root);
+            assert_eq!(project_config_path(&root.join("src/lib")), root.join(PROJECT_CONFIG_FILE));
+            let _ = fs::remove_dir_all(root);
+        }
+
+        #[test]
+        fn project_root_outside_a_repository_is_the_analyzed_directory() {
+            let root = project("config-no-git", &[("lib/math.js", "")]);
+            assert_eq!(project_root(&root.join("lib/math.js")), root.join("lib"));
+            assert_eq!(project_root(&root.join("lib")), root.join("lib"));
+            let _ = fs::remove_dir_all(root);
+        }
+    }
+}
@@ -0,0 +1,15 @@
+pub mod config_dir {
+    use std::env;
+    use std::path::PathBuf;
+
+    /// User configuration directory, `$XDG_CONFIG_HOME/wasm-grate` or `~/.config/wasm-grate`.
+    /// Only looked up, it is created when something is stored.
+    pub fn config_directory() -> std::io::Result<PathBuf> {
+        let config_home = env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .filter(|path| path.is_absolute())
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+            .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, "neither XDG_CONFIG_HOME nor HOME is set"))?;
+        Ok(config_home.join("wasm-grate"))
+    }
+}
@@ -0,0 +1,8 @@
+pub mod exit_code {
+    /// More findings than `--max-findings`, or one scoring at least `--fail-on-score`
+    pub const FINDINGS_OVER_LIMIT: i32 = 1;
+    /// Invalid arguments or unreadable configuration, also used by clap for usage errors
+    pub const CONFIGURATION_ERROR: i32 = 2;
+    /// Some files could not be read or parsed, the results are incomplete
+    pub const PARSE_ERROR: i32 = 3;
+}
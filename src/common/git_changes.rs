@@ -0,0 +1,234 @@
+pub mod git_changes {
+    use std::collections::HashMap;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use std::process::Command;
+
+    /// Files changed relative to a git revision, with the line ranges of their changed hunks.
+    /// Uncommitted and untracked files count as changed.
+    #[derive(Debug, Default)]
+    pub struct ChangedFiles {
+        // Canonical path to inclusive ranges of lines in the current version of the file
+        files: HashMap<PathBuf, Vec<(usize, usize)>>,
+    }
+
+    impl ChangedFiles {
+        pub fn contains(&self, path: &Path) -> bool {
+            fs::canonicalize(path).is_ok_and(|path| self.files.contains_key(&path))
+        }
+
+        /// Whether lines `start..=end` of the file intersect one of its changed hunks.
+        pub fn touches(&self, path: &Path, start: usize, end: usize) -> bool {
+            let Some(hunks) = fs::canonicalize(path).ok().and_then(|path| self.files.get(&path)) else {
+                return false;
+            };
+            hunks.iter().any(|&(hunk_start, hunk_end)| hunk_start <= end && start <= hunk_end)
+        }
+    }
+
+    pub fn changed_since(revision: &str, path: &Path) -> Result<ChangedFiles, String> {
+        let toplevel = PathBuf::from(_git(_directory(path), &["rev-parse", "--show-toplevel"])?.trim());
+
+        let mut changes = ChangedFiles::default();
+        // Explicit prefixes, `diff.noprefix` or `diff.mnemonicPrefix` would change them
+        let diff = _git(&toplevel, &[
+            "diff", "--unified=0", "--no-color", "--no-ext-diff", "--no-renames", "--src-prefix=a/", "--dst-prefix=b/",
+            revision, "--",
+        ])?;
+        let mut current_file: Option<PathBuf> = None;
+        for line in diff.lines() {
+            if let Some(file) = line.strip_prefix("+++ ") {
+                // Deleted files have nothing left to analyze
+                // Paths with spaces end with a tab
+                current_file = _unquote(file.trim_end_matches('\t')).strip_prefix("b/").map(|file| toplevel.join(file));
+            } else if let (Some(file), Some(hunk)) = (&current_file, line.strip_prefix("@@ ")) {
+                if let Some(range) = _parse_hunk(hunk) {
+                    changes.files.entry(file.clone()).or_default().push(range);
+                }
+            }
+        }
+
+        let untracked = _git(&toplevel, &["ls-files", "--others", "--exclude-standard"])?;
+        for file in untracked.lines() {
+            changes.files.entry(toplevel.join(_unquote(file))).or_default().push((1, usize::MAX));
+        }
+
+        changes.files = changes.files.into_iter()
+            .filter_map(|(file, hunks)| fs::canonicalize(file).ok().map(|file| (file, hunks)))
+            .collect();
+        Ok(changes)
+    }
+
+    /// Commit checked out in the repository containing `path`, and whether the work tree has uncommitted changes.
+    pub fn head_commit(path: &Path) -> Option<(String, bool)> {
+        let commit = _git(_directory(path), &["rev-parse", "HEAD"]).ok()?;
+        let status = _git(_directory(path), &["status", "--porcelain", "--untracked-files=no"]).ok()?;
+        Some((commit.trim().to_string(), !status.trim().is_empty()))
+    }
+
+    /// Path as git prints it, unusual characters are escaped in double quotes like `"sp\303\244ce.js"`.
+    fn _unquote(path: &str) -> String {
+        let Some(quoted) = path.strip_prefix('"').and_then(|path| path.strip_suffix('"')) else {
+            return path.to_string();
+        };
+        let mut bytes = Vec::with_capacity(quoted.len());
+        let mut rest = quoted.as_bytes();
+        while let Some((&byte, after)) = rest.split_first() {
+            rest = after;
+            if byte != b'\\' {
+                bytes.push(byte);
+                continue;
+            }
+            let Some((&escaped, after)) = rest.split_first() else {
+                break;
+            };
+            rest = after;
+            let octal_digits = rest.iter().take(2).take_while(|digit| (b'0'..=b'7').contains(digit)).count();
+            match escaped {
+                // Bytes outside printable ASCII, three octal digits
+                b'0'..=b'3' if octal_digits == 2 => {
+                    bytes.push((escaped - b'0') * 64 + (rest[0] - b'0') * 8 + (rest[1] - b'0'));
+                    rest = &rest[2..];
+                }
+                b'a' => bytes.push(0x07),
+                b'b' => bytes.push(0x08),
+                b'f' => bytes.push(0x0c),
+                b'n' => bytes.push(b'\n'),
+                b'r' => bytes.push(b'\r'),
+                b't' => bytes.push(b'\t'),
+                b'v' => bytes.push(0x0b),
+                other => bytes.push(other),
+            }
+        }
+        String::from_utf8_lossy(&bytes).into_owned()
+    }
+
+    /// Line range in the new file of a hunk header like `-10,2 +12,3 @@`.
+    fn _parse_hunk(hunk: &str) -> Option<(usize, usize)> {
+        let new_range = hunk.split_whitespace().find_map(|part| part.strip_prefix('+'))?;
+        let (start, count) = match new_range.split_once(',') {
+            Some((start, count)) => (start.parse::<usize>().ok()?, count.parse::<usize>().ok()?),
+            None => (new_range.parse::<usize>().ok()?, 1),
+        };
+        if count == 0 {
+            // Pure deletion after line `start`, the lines around it changed
+            Some((start, start + 1))
+        } else {
+            Some((start, start + count - 1))
+        }
+    }
+
+    /// Directory git runs in for a file or directory path.
+    fn _directory(path: &Path) -> &Path {
+        let directory = if path.is_dir() { path } else { path.parent().unwrap_or(Path::new(".")) };
+        if directory.as_os_str().is_empty() { Path::new(".") } else { directory }
+    }
+
+    fn _git(directory: &Path, args: &[&str]) -> Result<String, String> {
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(directory)
+            .args(args)
+            .output()
+            .map_err(|e| format!("failed to run git: {}", e))?;
+        if !output.status.success() {
+            return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::fs;
+        use std::path::{Path, PathBuf};
+        use crate::fixtures::fixtures::project;
+        use super::{changed_since, _git, _parse_hunk, _unquote};
+
+        const ORIGINAL: &str = "function a() {\n  return 1;\n}\n\nfunction b() {\n  return 2;\n}\n";
+        const EDITED: &str = "function a() {\n  return 1;\n}\n\nfunction b() {\n  return 3;\n}\n";
+
+        /// Repository with `files` committed under the given git config, then edited to `EDITED`.
+        fn edited_repository(name: &str, files: &[&str], config: &[(&str, &str)]) -> PathBuf {
+            let contents: Vec<(&str, &str)> = files.iter().map(|file| (*file, ORIGINAL)).collect();
+            let root = project(name, &contents);
+            _git(&root, &["init", "--quiet"]).expect("Failed to create repository");
+            for (key, value) in [("user.name", "test"), ("user.email", "test@example.com"), ("commit.gpgsign", "false")].iter().chain(config) {
+                _git(&root, &["config", key, value]).expect("Failed to configure repository");
+            }
+            _git(&root, &["add", "--all"]).expect("Failed to stage fixtures");
+            _git(&root, &["commit", "--quiet", "--message", "fixtures"]).expect("Failed to commit fixtures");
+            for file in files {
+                fs::write(root.join(file), EDITED).expect("Failed to edit fixture");
+            }
+            root
+        }
+
+        fn assert_only_b_changed(root: &Path, file: &str) {
+            let changes = changed_since("HEAD", root).expect("Failed to diff repository");
+            let path = root.join(file);
+            assert!(changes.contains(&path), "{} is not changed", file);
+            assert!(changes.touches(&path, 5, 7));
+            assert!(!changes.touches(&path, 1, 3));
+        }
+
+        #[test]
+        fn changes_are_found_without_diff_prefixes() {
+            let root = edited_repository("changed-noprefix", &["lib/math.js"], &[("diff.noprefix", "true")]);
+            assert_only_b_changed(&root, "lib/math.js");
+            let _ = fs::remove_dir_all(root);
+        }
+
+        #[test]
+        fn changes_are_found_with_mnemonic_prefixes() {
+            let root = edited_repository("changed-mnemonic", &["lib/math.js"], &[("diff.mnemonicPrefix", "true")]);
+            assert_only_b_changed(&root, "lib/math.js");
+            let _ = fs::remove_dir_all(root);
+        }
+
+        #[test]
+        fn quoted_and_spaced_paths_are_found() {
+            let root = edited_repository("changed-quoted", &["sp\u{e4}ce.js", "with space.js"], &[("core.quotePath", "true")]);
+            fs::write(root.join("new \u{fc}.js"), ORIGINAL).expect("Failed to write untracked fixture");
+            assert_only_b_changed(&root, "sp\u{e4}ce.js");
+            assert_only_b_changed(&root, "with space.js");
+            let changes = changed_since("HEAD", &root).expect("Failed to diff repository");
+            assert!(changes.touches(&root.join("new \u{fc}.js"), 1, 1));
+            let _ = fs::remove_dir_all(root);
+        }
+
+        #[test]
+        fn git_quoted_paths_are_unquoted() {
+            let cases = [
+                ("b/plain.js", "b/plain.js"),
+                ("\"b/sp\\303\\244ce.js\"", "b/sp\u{e4}ce.js"),
+                ("\"b/tab\\there.js\"", "b/tab\there.js"),
+                ("\"b/quote\\\"d\\\\.js\"", "b/quote\"d\\.js"),
+            ];
+            for (path, expected) in cases {
+                assert_eq!(_unquote(path), expected, "path {:?}", path);
+            }
+        }
+
+        #[test]
+        fn hunk_headers_give_the_changed_lines_of_the_new_file() {
+            // Headers as passed in, after the leading `@@ `
+            let cases = [
+                ("-10,2 +12,3 @@", Some((12, 14))),
+                ("-10,2 +12,1 @@", Some((12, 12))),
+                ("-10,2 +12,3 @@ function mix(a, b) {", Some((12, 14))),
+                // Pure deletion, the lines around it
+                ("-10,2 +9,0 @@", Some((9, 10))),
+                ("-1,5 +0,0 @@", Some((0, 1))),
+                // Single line forms without a count
+                ("-10 +12 @@", Some((12, 12))),
+                ("-10,3 +12 @@", Some((12, 12))),
+                ("-0,0 +1,4 @@", Some((1, 4))),
+                ("-10,2 +x,3 @@", None),
+                ("-10,2 @@", None),
+            ];
+            for (hunk, expected) in cases {
+                assert_eq!(_parse_hunk(hunk), expected, "hunk {:?}", hunk);
+            }
+        }
+    }
+}
@@ -0,0 +1,224 @@
+pub mod history {
+    use serde::{Serialize, Deserialize};
+    use std::collections::HashMap;
+    use std::fs::{self, OpenOptions};
+    use std::io::Write;
+    use std::path::{Path, PathBuf};
+    use crate::common::config_dir::config_dir::config_directory;
+
+    const HISTORY_FILE: &str = "history.jsonl";
+
+    /// Summary of one recorded run, a line of the history file.
+    #[derive(Serialize, Deserialize, Debug, Clone)]
+    pub struct HistoryEntry {
+        /// Seconds since the Unix epoch
+        pub timestamp: u64,
+        /// Canonical root of the analyzed project
+        pub project: String,
+        /// Analyzed path relative to the project root
+        pub path: String,
+        #[serde(default)]
+        pub commit: Option<String>,
+        /// The work tree had uncommitted changes
+        #[serde(default)]
+        pub dirty: bool,
+        pub files: usize,
+        pub functions: usize,
+        pub findings: usize,
+        pub mean_score: f64,
+        pub max_score: usize,
+        pub total_score: usize,
+    }
+
+    impl HistoryEntry {
+        /// Short commit hash, marked with `+` when the work tree was dirty.
+        pub fn revision(&self) -> String {
+            match &self.commit {
+                Some(commit) => format!("{}{}", &commit[..commit.len().min(8)], if self.dirty { "+" } else { "" }),
+                None => "-".to_string(),
+            }
+        }
+    }
+
+    /// `history.jsonl` in the user configuration directory.
+    pub fn history_path() -> std::io::Result<PathBuf> {
+        Ok(config_directory()?.join(HISTORY_FILE))
+    }
+
+    pub fn append(path: &Path, entry: &HistoryEntry) -> std::io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        writeln!(file, "{}", serde_json::to_string(entry)?)
+    }
+
+    /// Runs recorded for the project, oldest first. A commit analyzed again without
+    /// local changes replaces its earlier run of the same path.
+    pub fn load(path: &Path, project: &str) -> Result<Vec<HistoryEntry>, String> {
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        let data = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+
+        let mut entries: Vec<HistoryEntry> = Vec::new();
+        let mut by_commit: HashMap<(String, String), usize> = HashMap::new();
+        for (number, line) in data.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()) {
+            let entry: HistoryEntry = serde_json::from_str(line)
+                .map_err(|e| format!("{}:{}: {}", path.display(), number + 1, e))?;
+            if entry.project != project {
+                continue;
+            }
+            match entry.commit.clone().filter(|_| !entry.dirty) {
+                Some(commit) => match by_commit.get(&(commit.clone(), entry.path.clone())) {
+                    Some(&index) => entries[index] = entry,
+                    None => {
+                        by_commit.insert((commit, entry.path.clone()), entries.len());
+                        entries.push(entry);
+                    }
+                },
+                None => entries.push(entry),
+            }
+        }
+        entries.sort_by_key(|entry| entry.timestamp);
+        Ok(entries)
+    }
+
+    /// UTC date and time like `2024-03-09 14:05`.
+    pub fn format_timestamp(timestamp: u64) -> String {
+        let days = (timestamp / 86_400) as i64;
+        let minutes_of_day = timestamp % 86_400 / 60;
+
+        // Civil date from days since 1970-01-01, in 400 year eras starting on March 1st
+        let shifted = days + 719_468;
+        let era = shifted.div_euclid(146_097);
+        let day_of_era = shifted.rem_euclid(146_097);
+        let year_of_era = (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let month_index = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+        let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+        let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+        format!("{:04}-{:02}-{:02} {:02}:{:02}", year, month, day, minutes_of_day / 60, minutes_of_day % 60)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::fs;
+        use crate::fixtures::fixtures::project;
+        use super::{append, format_timestamp, load, HistoryEntry};
+
+        fn entry(timestamp: u64, project: &str, path: &str, commit: Option<&str>, dirty: bool) -> HistoryEntry {
+            HistoryEntry {
+                timestamp,
+                project: project.to_string(),
+                path: path.to_string(),
+                commit: commit.map(str::to_string),
+                dirty,
+                files: 1,
+                functions: 2,
+                findings: 1,
+                mean_score: 3.5,
+                max_score: 7,
+                total_score: timestamp as usize,
+            }
+        }
+
+        fn timestamps(entries: &[HistoryEntry]) -> Vec<u64> {
+            entries.iter().map(|entry| entry.timestamp).collect()
+        }
+
+        #[test]
+        fn timestamps_format_as_utc_dates() {
+            for (timestamp, expected) in [
+                (0, "1970-01-01 00:00"),
+                (59, "1970-01-01 00:00"),
+                (1_709_210_096, "2024-02-29 12:34"),
+                (951_868_740, "2000-02-29 23:59"),
+                (951_868_800, "2000-03-01 00:00"),
+                (1_704_067_199, "2023-12-31 23:59"),
+                (4_107_542_400, "2100-03-01 00:00"),
+            ] {
+                assert_eq!(format_timestamp(timestamp), expected, "case {:?}", timestamp);
+            }
+        }
+
+        #[test]
+        fn revision_is_the_short_commit_marked_when_dirty() {
+            assert_eq!(entry(0, "/p", ".", Some("0123456789abcdef"), false).revision(), "01234567");
+            assert_eq!(entry(0, "/p", ".", Some("0123456789abcdef"), true).revision(), "01234567+");
+            assert_eq!(entry(0, "/p", ".", Some("abc"), false).revision(), "abc");
+            assert_eq!(entry(0, "/p", ".", None, false).revision(), "-");
+        }
+
+        #[test]
+        fn missing_history_is_empty() {
+            let root = project("history-missing", &[]);
+            assert!(load(&root.join("history.jsonl"), "/p").unwrap().is_empty());
+            let _ = fs::remove_dir_all(root);
+        }
+
+        #[test]
+        fn clean_runs_of_the_same_commit_and_path_replace_each_other() {
+            let root = project("history-dedup", &[]);
+            let path = root.join("nested/history.jsonl");
+            for recorded in [
+                entry(10, "/p", ".", Some("aaa"), false),
+                entry(20, "/p", "src", Some("aaa"), false),
+                entry(30, "/p", ".", Some("aaa"), false),
+                entry(40, "/p", ".", Some("bbb"), false),
+            ] {
+                append(&path, &recorded).expect("Failed to append entry");
+            }
+            let entries = load(&path, "/p").unwrap();
+            // The rerun at 30 takes the place of the run at 10, other paths and commits stay
+            assert_eq!(timestamps(&entries), vec![20, 30, 40]);
+            assert_eq!(entries[1].total_score, 30);
+            let _ = fs::remove_dir_all(root);
+        }
+
+        #[test]
+        fn dirty_runs_and_runs_without_commit_are_all_kept() {
+            let root = project("history-dirty", &[]);
+            let path = root.join("history.jsonl");
+            for recorded in [
+                entry(10, "/p", ".", Some("aaa"), true),
+                entry(20, "/p", ".", Some("aaa"), true),
+                entry(30, "/p", ".", Some("aaa"), false),
+                entry(40, "/p", ".", None, false),
+                entry(50, "/p", ".", None, false),
+            ] {
+                append(&path, &recorded).expect("Failed to append entry");
+            }
+            assert_eq!(timestamps(&load(&path, "/p").unwrap()), vec![10, 20, 30, 40, 50]);
+            let _ = fs::remove_dir_all(root);
+        }
+
+        #[test]
+        fn only_runs_of_the_project_are_loaded_oldest_first() {
+            let root = project("history-project", &[]);
+            let path = root.join("history.jsonl");
+            for recorded in [
+                entry(30, "/p", ".", Some("ccc"), false),
+                entry(20, "/other", ".", Some("aaa"), false),
+                entry(10, "/p", ".", Some("aaa"), false),
+                entry(40, "/p/sub", ".", Some("aaa"), false),
+            ] {
+                append(&path, &recorded).expect("Failed to append entry");
+            }
+            assert_eq!(timestamps(&load(&path, "/p").unwrap()), vec![10, 30]);
+            assert_eq!(timestamps(&load(&path, "/other").unwrap()), vec![20]);
+            let _ = fs::remove_dir_all(root);
+        }
+
+        #[test]
+        fn blank_lines_are_skipped_and_corrupt_lines_name_their_number() {
+            let valid = serde_json::to_string(&entry(10, "/p", ".", None, false)).unwrap();
+            let root = project("history-corrupt", &[("history.jsonl", &format!("{}\n\n{{ not json\n", valid))]);
+            let error = load(&root.join("history.jsonl"), "/p").expect_err("Corrupt line must fail");
+            assert!(error.contains("history.jsonl:3:"), "{}", error);
+            let _ = fs::remove_dir_all(root);
+        }
+    }
+}
@@ -1,15 +1,36 @@
 pub mod metrics {
+    use serde::{Serialize, Deserialize};
+
+    /// Threshold names accepted by `wasm-grate config` and `--threshold`.
+    pub const THRESHOLD_KEYS: [&str; 5] = [
+        "cyclomatic_complexity",
+        "cognitive_complexity",
+        "loop_depth",
+        "arithmetic_operations",
+        "string_operations",
+    ];
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+    #[serde(default)]
     pub struct Metrics {
         pub cyclomatic_complexity: usize,
+        pub cognitive_complexity: usize,
         pub loop_depth: usize,
         pub arithmetic_operations: usize,
         pub string_operations: usize,
     }
 
+    impl Default for Metrics {
+        fn default() -> Self {
+            Metrics::new()
+        }
+    }
+
     impl Metrics {
         pub fn new() -> Self {
             Metrics {
                 cyclomatic_complexity: 3,
+                cognitive_complexity: 5,
                 loop_depth: 1,
                 arithmetic_operations: 3,
                 string_operations: 1,
@@ -18,10 +39,35 @@ pub mod metrics {
 
         pub fn average(&self) -> usize {
             let total = self.cyclomatic_complexity
+                + self.cognitive_complexity
                 + self.loop_depth
                 + self.arithmetic_operations
                 + self.string_operations;
-            total / 4
+            total / 5
+        }
+
+        pub fn get(&self, key: &str) -> Option<usize> {
+            match key {
+                "cyclomatic_complexity" => Some(self.cyclomatic_complexity),
+                "cognitive_complexity" => Some(self.cognitive_complexity),
+                "loop_depth" => Some(self.loop_depth),
+                "arithmetic_operations" => Some(self.arithmetic_operations),
+                "string_operations" => Some(self.string_operations),
+                _ => None,
+            }
+        }
+
+        pub fn set(&mut self, key: &str, value: usize) -> Result<(), String> {
+            let threshold = match key {
+                "cyclomatic_complexity" => &mut self.cyclomatic_complexity,
+                "cognitive_complexity" => &mut self.cognitive_complexity,
+                "loop_depth" => &mut self.loop_depth,
+                "arithmetic_operations" => &mut self.arithmetic_operations,
+                "string_operations" => &mut self.string_operations,
+                _ => return Err(format!("unknown threshold '{}', expected one of: {}", key, THRESHOLD_KEYS.join(", "))),
+            };
+            *threshold = value;
+            Ok(())
         }
     }
-}
\ No newline at end of file
+}
@@ -1,5 +1,23 @@
+#[allow(clippy::module_inception)]
 pub mod metrics;
 pub use metrics::metrics::Metrics;
 
-pub mod create_config_dir;
-pub use create_config_dir::create_config_dir::create_config_directory;
+#[allow(clippy::module_inception)]
+pub mod config_dir;
+
+#[allow(clippy::module_inception)]
+pub mod config;
+
+#[allow(clippy::module_inception)]
+pub mod baseline;
+pub use baseline::baseline::Baseline;
+
+#[allow(clippy::module_inception)]
+pub mod exit_code;
+
+#[allow(clippy::module_inception)]
+pub mod git_changes;
+pub use git_changes::git_changes::{changed_since, head_commit, ChangedFiles};
+
+#[allow(clippy::module_inception)]
+pub mod history;
@@ -0,0 +1,68 @@
+pub mod fixtures {
+    use std::fs;
+    use std::path::PathBuf;
+    use swc_common::BytePos;
+    use swc_common::comments::Comments;
+    use swc_common::input::StringInput;
+    use swc_ecma_ast::{Decl, FnDecl, Module, ModuleItem, Stmt};
+    use swc_ecma_parser::{Parser, Syntax};
+    use swc_ecma_visit::VisitWith;
+    use crate::Metrics;
+    use crate::visitor::{FunctionAnalysis, FunctionAnalysisVisitor};
+
+    /// Every metric is at or above a zero threshold, so each score term is its weighted metric
+    pub const ZERO_THRESHOLDS: Metrics = Metrics {
+        cyclomatic_complexity: 0,
+        cognitive_complexity: 0,
+        loop_depth: 0,
+        arithmetic_operations: 0,
+        string_operations: 0,
+    };
+
+    /// Parses a JavaScript fixture as a module.
+    pub fn parse(source: &str) -> Module {
+        parse_with(source, Syntax::default())
+    }
+
+    pub fn parse_with(source: &str, syntax: Syntax) -> Module {
+        parse_at(source, syntax, BytePos(0), None)
+    }
+
+    /// Parses a fixture placed at `start` of a source map, collecting its comments.
+    pub fn parse_at(source: &str, syntax: Syntax, start: BytePos, comments: Option<&dyn Comments>) -> Module {
+        let input = StringInput::new(source, start, start + BytePos(source.len() as u32));
+        Parser::new(syntax, input, comments).parse_module().expect("Failed to parse fixture")
+    }
+
+    /// The function declaration the fixture starts with.
+    pub fn first_function(module: &Module) -> &FnDecl {
+        match &module.body[0] {
+            ModuleItem::Stmt(Stmt::Decl(Decl::Fn(fn_decl))) => fn_decl,
+            _ => panic!("Fixture must start with a function declaration"),
+        }
+    }
+
+    /// Analyses of the functions of a JavaScript fixture, in source order.
+    pub fn analyze(source: &str) -> Vec<FunctionAnalysis> {
+        analyze_module(&parse(source))
+    }
+
+    pub fn analyze_module(module: &Module) -> Vec<FunctionAnalysis> {
+        let mut visitor = FunctionAnalysisVisitor::new();
+        module.visit_with(&mut visitor);
+        visitor.functions
+    }
+
+    /// Fresh directory under the system temp dir holding the given files, unique to the test process.
+    pub fn project(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("wasm-grate-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).expect("Failed to create fixture project");
+        for (path, content) in files {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).expect("Failed to create fixture directory");
+            fs::write(path, content).expect("Failed to write fixture");
+        }
+        fs::canonicalize(root).expect("Failed to create fixture project")
+    }
+}
@@ -0,0 +1,3 @@
+#[allow(clippy::module_inception)]
+pub mod server;
+pub use server::server::run_server;
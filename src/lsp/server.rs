@@ -0,0 +1,344 @@
+pub mod server {
+    use std::collections::HashMap;
+    use std::error::Error;
+    use std::path::{Path, PathBuf};
+    use lsp_server::{Connection, ErrorCode, Message, Notification, Request, Response};
+    use lsp_types::notification::{
+        DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, Notification as _, PublishDiagnostics,
+    };
+    use lsp_types::request::{CodeLensRequest, HoverRequest, Request as _};
+    use lsp_types::{
+        CodeLens, CodeLensOptions, CodeLensParams, Command, Diagnostic, DiagnosticSeverity, DidChangeTextDocumentParams,
+        DidCloseTextDocumentParams, DidOpenTextDocumentParams, Hover, HoverContents, HoverParams, HoverProviderCapability,
+        MarkupContent, MarkupKind, Position, PublishDiagnosticsParams, Range, ServerCapabilities, TextDocumentSyncCapability,
+        TextDocumentSyncKind, Url,
+    };
+    use crate::Metrics;
+    use crate::common::config::config::resolve_config;
+    use crate::parsing::{select_findings, AnalysisOptions, Project};
+    use crate::visitor::{top_reason, FunctionAnalysis};
+    use crate::visitor::report::report::report::{format_metrics, format_type_summary, CONCERN, DANGER, WARNING};
+
+    /// A function of an open document, positions in the UTF-16 columns editors use.
+    struct DocumentFunction {
+        /// Whole function
+        range: Range,
+        /// From the start of the function to the end of its first line
+        declaration: Range,
+        score: usize,
+        flagged: bool,
+        hover: String,
+    }
+
+    /// Serves diagnostics, code lenses and hovers for the open JS and TS documents over stdin and stdout.
+    pub fn run_server() -> Result<(), Box<dyn Error + Sync + Send>> {
+        let (connection, io_threads) = Connection::stdio();
+        let capabilities = ServerCapabilities {
+            text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+            code_lens_provider: Some(CodeLensOptions { resolve_provider: Some(false) }),
+            hover_provider: Some(HoverProviderCapability::Simple(true)),
+            ..ServerCapabilities::default()
+        };
+        connection.initialize(serde_json::to_value(capabilities)?)?;
+
+        let mut documents: HashMap<Url, Vec<DocumentFunction>> = HashMap::new();
+        for message in &connection.receiver {
+            match message {
+                Message::Request(request) => {
+                    if connection.handle_shutdown(&request)? {
+                        break;
+                    }
+                    let id = request.id.clone();
+                    let response = _handle_request(request, &documents).unwrap_or_else(|e| {
+                        Response::new_err(id, ErrorCode::InvalidParams as i32, e.to_string())
+                    });
+                    connection.sender.send(Message::Response(response))?;
+                }
+                Message::Notification(notification) => {
+                    let method = notification.method.clone();
+                    match _handle_notification(notification, &mut documents) {
+                        Ok(Some(uri)) => {
+                            let diagnostics = documents.get(&uri).map(|functions| _diagnostics(functions)).unwrap_or_default();
+                            let params = PublishDiagnosticsParams { uri, diagnostics, version: None };
+                            let notification = Notification::new(PublishDiagnostics::METHOD.to_string(), params);
+                            connection.sender.send(Message::Notification(notification))?;
+                        }
+                        Ok(None) => {}
+                        Err(e) => eprintln!("Error reading {}: {}", method, e),
+                    }
+                }
+                Message::Response(_) => {}
+            }
+        }
+        // The writer thread ends once every sender is gone
+        drop(connection);
+        io_threads.join()?;
+        Ok(())
+    }
+
+    fn _handle_request(request: Request, documents: &HashMap<Url, Vec<DocumentFunction>>) -> Result<Response, serde_json::Error> {
+        match request.method.as_str() {
+            CodeLensRequest::METHOD => {
+                let params: CodeLensParams = serde_json::from_value(request.params)?;
+                let lenses: Vec<CodeLens> = documents.get(&params.text_document.uri).into_iter().flatten()
+                    .map(|function| CodeLens {
+                        range: function.declaration,
+                        command: Some(Command {
+                            title: if function.flagged {
+                                format!("WASM candidate {}/10", function.score)
+                            } else {
+                                format!("WASM score {}/10", function.score)
+                            },
+                            command: String::new(),
+                            arguments: None,
+                        }),
+                        data: None,
+                    })
+                    .collect();
+                Ok(Response::new_ok(request.id, lenses))
+            }
+            HoverRequest::METHOD => {
+                let params: HoverParams = serde_json::from_value(request.params)?;
+                let position = params.text_document_position_params.position;
+                // The innermost function under the cursor
+                let hover = documents.get(&params.text_document_position_params.text_document.uri).into_iter().flatten()
+                    .filter(|function| function.range.start <= position && position <= function.range.end)
+                    .max_by_key(|function| function.range.start)
+                    .map(|function| Hover {
+                        contents: HoverContents::Markup(MarkupContent { kind: MarkupKind::Markdown, value: function.hover.clone() }),
+                        range: Some(function.declaration),
+                    });
+                Ok(Response::new_ok(request.id, hover))
+            }
+            _ => Ok(Response::new_err(
+                request.id,
+                ErrorCode::MethodNotFound as i32,
+                format!("unsupported request {}", request.method),
+            )),
+        }
+    }
+
+    /// Updates the analysis of the document the notification is about, returns its URI when its diagnostics changed.
+    fn _handle_notification(
+        notification: Notification,
+        documents: &mut HashMap<Url, Vec<DocumentFunction>>,
+    ) -> Result<Option<Url>, serde_json::Error> {
+        let (uri, text) = match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let params: DidOpenTextDocumentParams = serde_json::from_value(notification.params)?;
+                (params.text_document.uri, params.text_document.text)
+            }
+            DidChangeTextDocument::METHOD => {
+                let params: DidChangeTextDocumentParams = serde_json::from_value(notification.params)?;
+                // Full synchronization, the last change holds the whole text
+                let Some(change) = params.content_changes.into_iter().last() else {
+                    return Ok(None);
+                };
+                (params.text_document.uri, change.text)
+            }
+            DidCloseTextDocument::METHOD => {
+                let params: DidCloseTextDocumentParams = serde_json::from_value(notification.params)?;
+                documents.remove(&params.text_document.uri);
+                return Ok(Some(params.text_document.uri));
+            }
+            _ => return Ok(None),
+        };
+
+        let path = uri.to_file_path().unwrap_or_else(|_| PathBuf::from(uri.path()));
+        documents.insert(uri.clone(), _analyze_document(&path, text));
+        Ok(Some(uri))
+    }
+
+    fn _analyze_document(path: &Path, text: String) -> Vec<DocumentFunction> {
+        let thresholds = resolve_config(path).map(|resolved| resolved.thresholds).unwrap_or_else(|e| {
+            eprintln!("Error reading configuration: {}", e);
+            Metrics::new()
+        });
+        let project = Project::from_source(path, text);
+        for e in project.parse_errors() {
+            eprintln!("Error parsing {}", e);
+        }
+
+        let modules = project.modules();
+        let selection = select_findings(&modules, &project.source_map, &thresholds, &AnalysisOptions::default());
+        let (Some(module), Some(file)) = (modules.first(), project.source_map.files().first().cloned()) else {
+            return Vec::new();
+        };
+        let text: &str = &file.src;
+
+        module.functions.iter().enumerate()
+            .map(|(index, function)| {
+                let flagged_score = selection.findings.iter()
+                    .find(|(id, _)| id.function == index)
+                    .map(|&(_, score)| score);
+                let score = flagged_score.unwrap_or_else(|| selection.model.score(function));
+                let start = (function.span.lo - file.start_pos).0 as usize;
+                let end = (function.span.hi - file.start_pos).0 as usize;
+                let line_end = text[start..].find('\n').map(|length| start + length).unwrap_or(text.len()).min(end);
+                DocumentFunction {
+                    range: Range::new(_position(text, start), _position(text, end)),
+                    declaration: Range::new(_position(text, start), _position(text, line_end)),
+                    score,
+                    flagged: flagged_score.is_some(),
+                    hover: _hover(function, score, flagged_score.is_some(), &thresholds),
+                }
+            })
+            .collect()
+    }
+
+    fn _hover(function: &FunctionAnalysis, score: usize, flagged: bool, thresholds: &Metrics) -> String {
+        format!(
+            "**wasm-grate** {}/10{}\n\nTop reason: {}\n\nMetrics: {}\n\nNumeric data: {}",
+            score,
+            if flagged { ", WASM candidate" } else { "" },
+            top_reason(function, thresholds),
+            format_metrics(function),
+            format_type_summary(function)
+        )
+    }
+
+    fn _diagnostics(functions: &[DocumentFunction]) -> Vec<Diagnostic> {
+        functions.iter()
+            .filter(|function| function.flagged)
+            .map(|function| Diagnostic {
+                range: function.declaration,
+                severity: Some(_severity(function.score)),
+                source: Some("wasm-grate".to_string()),
+                message: format!("WASM candidate, complexity {}/10", function.score),
+                ..Diagnostic::default()
+            })
+            .collect()
+    }
+
+    fn _severity(score: usize) -> DiagnosticSeverity {
+        match score {
+            _ if score >= DANGER => DiagnosticSeverity::ERROR,
+            _ if score >= WARNING => DiagnosticSeverity::WARNING,
+            _ if score >= CONCERN => DiagnosticSeverity::INFORMATION,
+            _ => DiagnosticSeverity::HINT,
+        }
+    }
+
+    /// Line and UTF-16 column of a byte offset.
+    fn _position(text: &str, offset: usize) -> Position {
+        let before = &text[..offset.min(text.len())];
+        let line_start = before.rfind('\n').map(|newline| newline + 1).unwrap_or(0);
+        Position::new(
+            before.matches('\n').count() as u32,
+            before[line_start..].encode_utf16().count() as u32,
+        )
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::collections::HashMap;
+        use std::fs;
+        use std::path::PathBuf;
+        use lsp_server::{Notification, Request, RequestId};
+        use lsp_types::notification::{DidCloseTextDocument, DidOpenTextDocument, Notification as _};
+        use lsp_types::request::{CodeLensRequest, HoverRequest, Request as _};
+        use lsp_types::{DiagnosticSeverity, Position, Range, Url};
+        use serde_json::{json, Value};
+        use crate::fixtures::fixtures::project;
+        use super::{_analyze_document, _diagnostics, _handle_notification, _handle_request, _position};
+
+        // `hot` follows a line with an emoji, two UTF-16 units for four bytes
+        const DOCUMENT: &str = "const smile = \"😀\"; function hot(a) {\n  for (;;) { for (;;) { a = a * 2 + 1; } }\n}\nfunction cold() {}\n";
+        // Only `hot` goes over them, whatever the user config says
+        const PROJECT_CONFIG: &str = r#"{ "thresholds": {
+            "cyclomatic_complexity": 1, "cognitive_complexity": 0, "loop_depth": 0, "arithmetic_operations": 0, "string_operations": 0
+        } }"#;
+
+        fn document_project(name: &str) -> PathBuf {
+            project(name, &[(".git/HEAD", ""), (".wasm-grate.json", PROJECT_CONFIG), ("src/doc.js", DOCUMENT)])
+        }
+
+        #[test]
+        fn positions_count_lines_and_utf16_columns() {
+            let text = "ab\nçé😀x\n";
+            for (offset, expected) in [
+                (0, (0, 0)),
+                (2, (0, 2)),
+                (3, (1, 0)),
+                // ç and é are two bytes but one unit, 😀 four bytes but two units
+                (5, (1, 1)),
+                (7, (1, 2)),
+                (11, (1, 4)),
+                (12, (1, 5)),
+                (13, (2, 0)),
+                (100, (2, 0)),
+            ] {
+                assert_eq!(_position(text, offset), Position::new(expected.0, expected.1), "case {:?}", offset);
+            }
+        }
+
+        #[test]
+        fn document_functions_carry_ranges_scores_and_hovers() {
+            let root = document_project("lsp-analyze");
+            let functions = _analyze_document(&root.join("src/doc.js"), DOCUMENT.to_string());
+            assert_eq!(functions.len(), 2);
+
+            let hot = &functions[0];
+            // "const smile = \"😀\"; " is 20 UTF-16 units
+            assert_eq!(hot.range, Range::new(Position::new(0, 20), Position::new(2, 1)));
+            assert_eq!(hot.declaration, Range::new(Position::new(0, 20), Position::new(0, 37)));
+            assert!(hot.flagged);
+            assert_eq!(hot.score, 10);
+            assert!(hot.hover.starts_with("**wasm-grate** 10/10, WASM candidate"), "{}", hot.hover);
+
+            let cold = &functions[1];
+            assert_eq!(cold.range, Range::new(Position::new(3, 0), Position::new(3, 18)));
+            assert!(!cold.flagged);
+            assert!(!cold.hover.contains("WASM candidate"), "{}", cold.hover);
+
+            let diagnostics = _diagnostics(&functions);
+            assert_eq!(diagnostics.len(), 1);
+            assert_eq!(diagnostics[0].range, hot.declaration);
+            assert_eq!(diagnostics[0].severity, Some(DiagnosticSeverity::ERROR));
+            let _ = fs::remove_dir_all(root);
+        }
+
+        #[test]
+        fn unparsable_document_has_no_functions() {
+            let root = document_project("lsp-broken");
+            assert!(_analyze_document(&root.join("src/doc.js"), "function (".to_string()).is_empty());
+            let _ = fs::remove_dir_all(root);
+        }
+
+        #[test]
+        fn opened_documents_answer_hovers_and_code_lenses() {
+            let root = document_project("lsp-requests");
+            let uri = Url::from_file_path(root.join("src/doc.js")).unwrap();
+            let mut documents = HashMap::new();
+            let open = Notification::new(
+                DidOpenTextDocument::METHOD.to_string(),
+                json!({ "textDocument": { "uri": uri, "languageId": "javascript", "version": 1, "text": DOCUMENT } }),
+            );
+            assert_eq!(_handle_notification(open, &mut documents).unwrap(), Some(uri.clone()));
+
+            let request = |method: &str, params: Value| {
+                let response = _handle_request(Request::new(RequestId::from(1), method.to_string(), params), &documents).unwrap();
+                assert!(response.error.is_none(), "{:?}", response.error);
+                response.result.unwrap()
+            };
+            let hover_at = |line: u32, character: u32| {
+                request(HoverRequest::METHOD, json!({ "textDocument": { "uri": uri }, "position": { "line": line, "character": character } }))
+            };
+            let hover = hover_at(1, 10);
+            assert!(hover["contents"]["value"].as_str().unwrap().contains("WASM candidate"), "{}", hover);
+            assert_eq!(hover["range"]["start"], json!({ "line": 0, "character": 20 }));
+            // Before the function on its first line
+            assert_eq!(hover_at(0, 5), Value::Null);
+
+            let lenses = request(CodeLensRequest::METHOD, json!({ "textDocument": { "uri": uri } }));
+            let titles: Vec<&str> = lenses.as_array().unwrap().iter().map(|lens| lens["command"]["title"].as_str().unwrap()).collect();
+            assert_eq!(titles[0], "WASM candidate 10/10");
+            assert!(titles[1].starts_with("WASM score "), "{}", titles[1]);
+
+            let close = Notification::new(DidCloseTextDocument::METHOD.to_string(), json!({ "textDocument": { "uri": uri } }));
+            assert_eq!(_handle_notification(close, &mut documents).unwrap(), Some(uri.clone()));
+            assert!(documents.is_empty());
+            let _ = fs::remove_dir_all(root);
+        }
+    }
+}
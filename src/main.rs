@@ -1,51 +1,240 @@
 extern crate clap;
 
-use clap::{Arg, ArgAction, Command};
-use crate::common::{create_config_directory, Metrics};
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use clap::parser::ValueSource;
+use std::fs::File;
+use std::io::{BufWriter, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use crate::common::{changed_since, head_commit, Baseline, Metrics};
+use crate::common::config::config::{config_path, project_root, resolve_config, ConfigFile, ConfigLayer, ResolvedConfig};
+use crate::common::history::history::{self, HistoryEntry};
+use crate::common::metrics::metrics::THRESHOLD_KEYS;
+use crate::common::exit_code::exit_code;
+use crate::visitor::NormalizationMode;
+use crate::visitor::report::ReportFormat;
 
 mod parsing;
 mod common;
 mod visitor;
+mod lsp;
+#[cfg(test)]
+#[allow(clippy::module_inception)]
+mod fixtures;
 
 
 use dialoguer::Input;
-use crate::common::create_config_dir::create_config_dir::delete_max_scores;
 
-fn interactive_configuration() -> Metrics {
-    println!("Enter the thresholds for the metrics (press Enter to use default values):");
+fn interactive_configuration(current: &Metrics) -> Metrics {
+    println!("Enter the thresholds for the metrics (press Enter to keep the current values):");
 
     let cyclomatic_complexity: usize = Input::new()
         .with_prompt("Max allowed cyclomatic Complexity")
-        .default(3)
+        .default(current.cyclomatic_complexity)
+        .interact()
+        .unwrap();
+
+    let cognitive_complexity: usize = Input::new()
+        .with_prompt("Max allowed cognitive Complexity")
+        .default(current.cognitive_complexity)
         .interact()
         .unwrap();
 
     let loop_depth: usize = Input::new()
         .with_prompt("Max allowed loop depth")
-        .default(1)
+        .default(current.loop_depth)
         .interact()
         .unwrap();
 
     let arithmetic_operations: usize = Input::new()
         .with_prompt("Max allowed arithmetic operations")
-        .default(3)
+        .default(current.arithmetic_operations)
         .interact()
         .unwrap();
 
     let string_operations: usize = Input::new()
         .with_prompt("Max allowed string operations")
-        .default(1)
+        .default(current.string_operations)
         .interact()
         .unwrap();
 
-    return Metrics {
+    Metrics {
         cyclomatic_complexity,
+        cognitive_complexity,
         loop_depth,
         arithmetic_operations,
         string_operations,
     }
 }
 
+/// Name of the source read from standard input without --stdin-filename.
+const STDIN_FILENAME: &str = "<stdin>";
+/// Options that need files on disk or an interactive standard input.
+const STDIN_CONFLICTS: [&str; 4] = ["config", "watch", "changed-since", "record-history"];
+
+fn _parse_threshold(argument: &str) -> Result<(String, usize), String> {
+    let (key, value) = argument.split_once('=').ok_or("expected KEY=VALUE")?;
+    let value: usize = value.parse().map_err(|_| format!("'{}' is not a non-negative integer", value))?;
+    Metrics::new().set(key, value)?;
+    Ok((key.to_string(), value))
+}
+
+fn _parse_percent(argument: &str) -> Result<f32, String> {
+    match argument.parse::<f32>() {
+        Ok(percent) if (0.0..=100.0).contains(&percent) => Ok(percent),
+        _ => Err(format!("'{}' is not a percentage between 0 and 100", argument)),
+    }
+}
+
+/// The failing exit code of a finished analysis, if any.
+fn _exit_code(outcome: &parsing::AnalysisOutcome, fail_on_score: Option<usize>, max_findings: Option<usize>) -> Option<i32> {
+    // Incomplete results take precedence, passing the limits on them proves nothing
+    if outcome.parse_errors > 0 {
+        return Some(exit_code::PARSE_ERROR);
+    }
+    let over_score = fail_on_score.is_some_and(|fail_on_score| outcome.max_score.is_some_and(|max_score| max_score >= fail_on_score));
+    let over_count = max_findings.is_some_and(|max_findings| outcome.findings > max_findings);
+    (over_score || over_count).then_some(exit_code::FINDINGS_OVER_LIMIT)
+}
+
+fn _exit_with_configuration_error(message: String) -> ! {
+    eprintln!("Error: {}", message);
+    std::process::exit(exit_code::CONFIGURATION_ERROR);
+}
+
+fn _resolved_config(project_start: &Path) -> ResolvedConfig {
+    resolve_config(project_start).unwrap_or_else(|e| _exit_with_configuration_error(e))
+}
+
+/// `wasm-grate config get|set|list|reset`, on the user config unless `--project` is given
+fn run_config_command(matches: &ArgMatches) {
+    let cwd = std::env::current_dir().unwrap_or_else(|e| _exit_with_configuration_error(e.to_string()));
+
+    match matches.subcommand() {
+        Some(("get", args)) => {
+            let key = args.get_one::<String>("key").unwrap();
+            match _resolved_config(&cwd).thresholds.get(key) {
+                Some(value) => println!("{}", value),
+                None => _exit_with_configuration_error(format!("unknown threshold '{}', expected one of: {}", key, THRESHOLD_KEYS.join(", "))),
+            }
+        }
+        Some(("list", _)) => {
+            let resolved = _resolved_config(&cwd);
+            for key in THRESHOLD_KEYS {
+                let source = resolved.source(key);
+                let location = config_path(source, &cwd).map(|path| format!(", {}", path.display())).unwrap_or_default();
+                println!("{} = {} ({}{})", key, resolved.thresholds.get(key).unwrap_or_default(), source, location);
+            }
+        }
+        Some(("set", args)) => {
+            let key = args.get_one::<String>("key").unwrap();
+            let value = *args.get_one::<usize>("value").unwrap();
+            Metrics::new().set(key, value).unwrap_or_else(|e| _exit_with_configuration_error(e));
+            _update_config_file(args, &cwd, |config| {
+                config.thresholds.insert(key.clone(), value);
+            });
+        }
+        Some(("reset", args)) => {
+            let key = args.get_one::<String>("key");
+            if let Some(key) = key {
+                Metrics::new().set(key, 0).unwrap_or_else(|e| _exit_with_configuration_error(e));
+            }
+            _update_config_file(args, &cwd, |config| match key {
+                Some(key) => {
+                    config.thresholds.remove(key);
+                }
+                None => config.thresholds.clear(),
+            });
+        }
+        _ => unreachable!("clap requires a config subcommand"),
+    }
+}
+
+fn _update_config_file(args: &ArgMatches, cwd: &Path, update: impl FnOnce(&mut ConfigFile)) {
+    let layer = if args.get_flag("project") { ConfigLayer::Project } else { ConfigLayer::User };
+    let path = config_path(layer, cwd)
+        .unwrap_or_else(|| _exit_with_configuration_error("neither XDG_CONFIG_HOME nor HOME is set".to_string()));
+    let mut config = ConfigFile::load(&path).unwrap_or_else(|e| _exit_with_configuration_error(e));
+    update(&mut config);
+    config.store(&path).unwrap_or_else(|e| _exit_with_configuration_error(format!("{}: {}", path.display(), e)));
+}
+
+/// `wasm-grate history`, the recorded runs of the project with their changes from the previous run of the same path
+fn run_history_command(matches: &ArgMatches) {
+    let path = matches.get_one::<PathBuf>("path").unwrap();
+    let project = project_root(path).to_string_lossy().into_owned();
+    let history_path = history::history_path().unwrap_or_else(|e| _exit_with_configuration_error(e.to_string()));
+    let mut entries = history::load(&history_path, &project).unwrap_or_else(|e| _exit_with_configuration_error(e));
+    if entries.is_empty() {
+        println!("No runs of {} recorded yet, analyze it with --record-history", project);
+        return;
+    }
+    if let Some(&limit) = matches.get_one::<usize>("limit") {
+        entries.drain(..entries.len().saturating_sub(limit));
+    }
+
+    println!("{:<16}  {:<9}  {:<20}  {:>14}  {:>16}  {:>9}", "Date", "Commit", "Path", "Findings", "Mean score", "Max score");
+    for (index, entry) in entries.iter().enumerate() {
+        let previous = entries[..index].iter().rev().find(|previous| previous.path == entry.path);
+        let findings_change = previous
+            .map(|previous| format!(" ({:+})", entry.findings as i64 - previous.findings as i64))
+            .unwrap_or_default();
+        let score_change = previous
+            .map(|previous| format!(" ({:+.2})", entry.mean_score - previous.mean_score))
+            .unwrap_or_default();
+        println!(
+            "{:<16}  {:<9}  {:<20}  {:>14}  {:>16}  {:>9}",
+            history::format_timestamp(entry.timestamp),
+            entry.revision(),
+            entry.path,
+            format!("{}{}", entry.findings, findings_change),
+            format!("{:.2}{}", entry.mean_score, score_change),
+            entry.max_score
+        );
+    }
+
+    let (first, last) = (&entries[0], &entries[entries.len() - 1]);
+    if entries.len() > 1 && first.path == last.path {
+        println!(
+            "\nSince {}: findings {} -> {} ({:+}), total score {} -> {} ({:+})",
+            first.revision(),
+            first.findings,
+            last.findings,
+            last.findings as i64 - first.findings as i64,
+            first.total_score,
+            last.total_score,
+            last.total_score as i64 - first.total_score as i64
+        );
+    }
+}
+
+fn _record_history(input_path: &Path, summary: &visitor::report::Aggregate) -> Result<(), String> {
+    let project_path = project_root(input_path);
+    let analyzed = std::fs::canonicalize(input_path).map_err(|e| e.to_string())?;
+    let relative = analyzed.strip_prefix(&project_path).unwrap_or(&analyzed).to_string_lossy().replace('\\', "/");
+    let (commit, dirty) = match head_commit(input_path) {
+        Some((commit, dirty)) => (Some(commit), dirty),
+        None => (None, false),
+    };
+    let entry = HistoryEntry {
+        timestamp: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or_default(),
+        project: project_path.to_string_lossy().into_owned(),
+        path: if relative.is_empty() { ".".to_string() } else { relative },
+        commit,
+        dirty,
+        files: summary.files,
+        functions: summary.functions,
+        findings: summary.flagged,
+        mean_score: summary.mean_score,
+        max_score: summary.max_score,
+        total_score: summary.total_score(),
+    };
+    let history_path = history::history_path().map_err(|e| e.to_string())?;
+    history::append(&history_path, &entry).map_err(|e| format!("{}: {}", history_path.display(), e))
+}
+
 fn main() {
     let cmd = Command::new("wasm-grate")
         .version("0.3.2")
@@ -56,10 +245,33 @@ fn main() {
                 .short('p')
                 .long("path")
                 .value_name("FILE_OR_DIRECTORY")
-                .help("Sets the input file or directory to analyze (use '.' for the current directory)")
+                .help("Sets the input file or directory to analyze (use '.' for the current directory, '-' for standard input)")
                 .value_parser(clap::builder::NonEmptyStringValueParser::new())
                 .action(ArgAction::Set)
-                .required(true)
+                .required_unless_present("stdin")
+        )
+        .arg(
+            Arg::new("stdin")
+                .long("stdin")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("path")
+                .help("Analyzes the source read from standard input, same as --path -")
+        )
+        .arg(
+            Arg::new("stdin-filename")
+                .long("stdin-filename")
+                .value_name("FILE")
+                .value_parser(clap::value_parser!(PathBuf))
+                .help("Name of the source read from standard input, its extension picks the syntax and reports refer to it (JavaScript named <stdin> without it)")
+        )
+        .arg(
+            Arg::new("threshold")
+                .short('t')
+                .long("threshold")
+                .value_name("KEY=VALUE")
+                .value_parser(_parse_threshold)
+                .action(ArgAction::Append)
+                .help(format!("Overrides a threshold for this run, KEY is one of: {}", THRESHOLD_KEYS.join(", ")))
         )
         .arg(
             Arg::new("config")
@@ -67,23 +279,408 @@ fn main() {
                 .long("config")
                 .action(ArgAction::SetTrue)
                 .help("Enter configuration mode to interactively set metrics thresholds")
+        )
+        .arg(
+            Arg::new("report-unused-suppressions")
+                .long("report-unused-suppressions")
+                .action(ArgAction::SetTrue)
+                .help("Reports wasm-grate suppression comments that do not suppress any finding")
+        )
+        .arg(
+            Arg::new("baseline")
+                .long("baseline")
+                .value_name("FILE")
+                .value_parser(clap::value_parser!(PathBuf))
+                .help("Reports only findings that are new or worsened compared to the baseline file")
+        )
+        .arg(
+            Arg::new("write-baseline")
+                .long("write-baseline")
+                .value_name("FILE")
+                .value_parser(clap::value_parser!(PathBuf))
+                .help("Records the current findings in a baseline file")
+        )
+        .arg(
+            Arg::new("fail-on-score")
+                .long("fail-on-score")
+                .value_name("SCORE")
+                .value_parser(clap::builder::RangedU64ValueParser::<usize>::new().range(0..=10))
+                .help("Exits with code 1 if a reported finding scores at least SCORE (0-10)")
+        )
+        .arg(
+            Arg::new("max-findings")
+                .long("max-findings")
+                .value_name("COUNT")
+                .value_parser(clap::value_parser!(usize))
+                .help("Exits with code 1 if more than COUNT findings are reported")
+        )
+        .arg(
+            Arg::new("changed-since")
+                .long("changed-since")
+                .value_name("REVISION")
+                .value_parser(clap::builder::NonEmptyStringValueParser::new())
+                .help("Analyzes only files changed since the git revision, including uncommitted and untracked ones")
+        )
+        .arg(
+            Arg::new("changed-functions-only")
+                .long("changed-functions-only")
+                .action(ArgAction::SetTrue)
+                .requires("changed-since")
+                .help("With --changed-since, reports only functions intersecting a changed hunk")
+        )
+        .arg(
+            Arg::new("normalization")
+                .long("normalization")
+                .value_name("MODE")
+                .value_parser(NormalizationMode::NAMES)
+                .default_value("threshold-relative")
+                .help("How scores are mapped to 0-10: against the default thresholds (absolute), the active thresholds (threshold-relative), or the other functions of the project (project-percentile)")
+        )
+        .arg(
+            Arg::new("rank")
+                .long("rank")
+                .action(ArgAction::SetTrue)
+                .help("Sorts findings from the strongest and shows their percentile within the project")
+        )
+        .arg(
+            Arg::new("top")
+                .long("top")
+                .value_name("N")
+                .value_parser(clap::value_parser!(usize))
+                .help("Ranks findings and reports only the N strongest")
+        )
+        .arg(
+            Arg::new("top-percent")
+                .long("top-percent")
+                .value_name("P")
+                .value_parser(_parse_percent)
+                .help("Ranks findings and reports only those among the P% strongest functions of the project")
+        )
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .value_name("FORMAT")
+                .value_parser(ReportFormat::NAMES)
+                .default_value("text")
+                .help("Report format: text for the terminal, html for a self-contained page, markdown for pull request comments, csv or ndjson for spreadsheets and notebooks")
+        )
+        .arg(
+            Arg::new("all")
+                .long("all")
+                .action(ArgAction::SetTrue)
+                .help("With --format csv or ndjson, writes every analyzed function instead of only the findings")
+        )
+        .arg(
+            Arg::new("summary")
+                .long("summary")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("all")
+                .help("Reports counts, mean and max scores and flagged functions per directory and file instead of the individual findings")
+        )
+        .arg(
+            Arg::new("record-history")
+                .long("record-history")
+                .action(ArgAction::SetTrue)
+                .help("Appends the totals of this run to the history in the user config directory, see `wasm-grate history`")
+        )
+        .arg(
+            Arg::new("watch")
+                .long("watch")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(["output", "write-baseline", "record-history"])
+                .help("Keeps running, re-analyzes files as they change and prints which findings are new, resolved or changed their score")
+        )
+        .arg(
+            Arg::new("output")
+                .short('o')
+                .long("output")
+                .value_name("FILE")
+                .value_parser(clap::value_parser!(PathBuf))
+                .help("Writes the report to FILE instead of the standard output")
+        )
+        .subcommand_negates_reqs(true)
+        .args_conflicts_with_subcommands(true)
+        .subcommand(
+            Command::new("config")
+                .about("Reads or changes the stored metrics thresholds")
+                .subcommand_required(true)
+                .subcommand(Command::new("list").about("Lists all thresholds"))
+                .subcommand(
+                    Command::new("get")
+                        .about("Prints a threshold")
+                        .arg(Arg::new("key").required(true))
+                )
+                .subcommand(
+                    Command::new("set")
+                        .about("Stores a threshold")
+                        .arg(Arg::new("key").required(true))
+                        .arg(Arg::new("value").required(true).value_parser(clap::value_parser!(usize)))
+                        .arg(
+                            Arg::new("project")
+                                .long("project")
+                                .action(ArgAction::SetTrue)
+                                .help("Changes the project config at the repository root instead of the user config")
+                        )
+                )
+                .subcommand(
+                    Command::new("reset")
+                        .about("Restores the default of a threshold, or of all of them without a key")
+                        .arg(Arg::new("key"))
+                        .arg(
+                            Arg::new("project")
+                                .long("project")
+                                .action(ArgAction::SetTrue)
+                                .help("Changes the project config at the repository root instead of the user config")
+                        )
+                )
+        )
+        .subcommand(
+            Command::new("lsp")
+                .about("Runs a language server over stdin and stdout with diagnostics, code lenses and hovers for open documents")
+        )
+        .subcommand(
+            Command::new("history")
+                .about("Shows how the findings of the project changed over the runs recorded with --record-history")
+                .arg(
+                    Arg::new("path")
+                        .short('p')
+                        .long("path")
+                        .value_name("FILE_OR_DIRECTORY")
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .default_value(".")
+                        .help("A path inside the project, its git repository is the project")
+                )
+                .arg(
+                    Arg::new("limit")
+                        .long("limit")
+                        .value_name("N")
+                        .value_parser(clap::value_parser!(usize))
+                        .help("Shows only the N most recent runs")
+                )
         ).get_matches();
 
+    if let Some(("config", config_matches)) = cmd.subcommand() {
+        run_config_command(config_matches);
+        return;
+    }
+    if let Some(("lsp", _)) = cmd.subcommand() {
+        if let Err(e) = lsp::run_server() {
+            _exit_with_configuration_error(format!("language server: {}", e));
+        }
+        return;
+    }
+    if let Some(("history", history_matches)) = cmd.subcommand() {
+        run_history_command(history_matches);
+        return;
+    }
+
     let should_configure_interactively = *cmd.get_one::<bool>("config").unwrap_or(&false);
 
-    let thresholds = if should_configure_interactively {
-        // Delete existing max_scores.json file before interactive configuration
-        if let Ok(config_dir) = create_config_directory() {
-            if let Err(e) = delete_max_scores(config_dir) {
-                eprintln!("Error deleting max scores: {}", e);
-            }
+    let read_stdin = cmd.get_flag("stdin") || cmd.get_one::<String>("path").is_some_and(|path| path == "-");
+    let stdin_filename = cmd.get_one::<PathBuf>("stdin-filename");
+    if read_stdin {
+        if let Some(flag) = STDIN_CONFLICTS.iter().find(|flag| cmd.value_source(flag) == Some(ValueSource::CommandLine)) {
+            _exit_with_configuration_error(format!("--{} cannot be used with source read from standard input", flag));
         }
-        interactive_configuration()
+    } else if stdin_filename.is_some() {
+        _exit_with_configuration_error("--stdin-filename needs --stdin or --path -".to_string());
+    }
+
+    let input_path: String = match (read_stdin, stdin_filename) {
+        (true, Some(filename)) => filename.to_string_lossy().into_owned(),
+        (true, None) => STDIN_FILENAME.to_string(),
+        (false, _) => cmd.get_one::<String>("path").unwrap().to_string(),
+    };
+    if !read_stdin && !Path::new(&input_path).exists() {
+        eprintln!("Error: {} does not exist", input_path);
+        std::process::exit(exit_code::CONFIGURATION_ERROR);
+    }
+
+    // Defaults < user config < project config < --config / --threshold
+    let config_start = if read_stdin {
+        // The virtual file does not exist, its project is found from the directory it would be in
+        let cwd = std::env::current_dir().unwrap_or_else(|e| _exit_with_configuration_error(e.to_string()));
+        let virtual_path = cwd.join(&input_path);
+        virtual_path.parent().map(Path::to_path_buf).unwrap_or(cwd)
     } else {
-        Metrics::new()
+        PathBuf::from(&input_path)
     };
+    let mut thresholds = _resolved_config(&config_start).thresholds;
+    if should_configure_interactively {
+        // Prompts would wait forever for input that never comes in scripts and CI
+        if !std::io::stdin().is_terminal() {
+            _exit_with_configuration_error(
+                "--config needs an interactive terminal, use `wasm-grate config set` or --threshold instead".to_string()
+            );
+        }
+        thresholds = interactive_configuration(&thresholds);
+    }
+    for (key, value) in cmd.get_many::<(String, usize)>("threshold").into_iter().flatten() {
+        // Keys were validated while parsing the arguments
+        let _ = thresholds.set(key, *value);
+    }
+
+    let baseline = cmd.get_one::<PathBuf>("baseline").map(|baseline_path| {
+        Baseline::load(baseline_path).unwrap_or_else(|e| {
+            eprintln!("Error reading baseline {}: {}", baseline_path.display(), e);
+            std::process::exit(exit_code::CONFIGURATION_ERROR);
+        })
+    });
 
-    let input_path: String = cmd.get_one::<String>("path").unwrap().to_string();
+    let changed_files = cmd.get_one::<String>("changed-since").map(|revision| {
+        changed_since(revision, Path::new(&input_path)).unwrap_or_else(|e| {
+            eprintln!("Error reading changes since {}: {}", revision, e);
+            std::process::exit(exit_code::CONFIGURATION_ERROR);
+        })
+    });
 
-    parsing::process_input(input_path, &thresholds);
+    let top = cmd.get_one::<usize>("top").copied();
+    let top_percent = cmd.get_one::<f32>("top-percent").copied();
+    let ranking = (cmd.get_flag("rank") || top.is_some() || top_percent.is_some())
+        .then_some(parsing::Ranking { top, top_percent });
+
+    let format: ReportFormat = cmd.get_one::<String>("format").unwrap().parse().unwrap_or_default();
+    if cmd.get_flag("all") && !format.lists_all_functions() {
+        _exit_with_configuration_error(format!("--all needs --format csv or ndjson, not {}", format));
+    }
+    if cmd.get_flag("watch") && format != ReportFormat::Text {
+        _exit_with_configuration_error(format!("--watch writes text reports, not {}", format));
+    }
+
+    let options = parsing::AnalysisOptions {
+        report_unused_suppressions: cmd.get_flag("report-unused-suppressions"),
+        baseline,
+        write_baseline: cmd.get_one::<PathBuf>("write-baseline").cloned(),
+        changed_files,
+        changed_functions_only: cmd.get_flag("changed-functions-only"),
+        normalization: cmd.get_one::<String>("normalization").unwrap().parse().unwrap_or_default(),
+        ranking,
+        format,
+        all_functions: cmd.get_flag("all"),
+        summary_only: cmd.get_flag("summary"),
+    };
+
+    let mut out: Box<dyn Write> = match cmd.get_one::<PathBuf>("output") {
+        Some(output_path) => {
+            let file = File::create(output_path).unwrap_or_else(|e| {
+                _exit_with_configuration_error(format!("cannot write {}: {}", output_path.display(), e))
+            });
+            Box::new(BufWriter::new(file))
+        }
+        None => Box::new(std::io::stdout().lock()),
+    };
+    // Escape codes only make sense in a terminal report
+    if options.format != ReportFormat::Text || cmd.contains_id("output") {
+        colored::control::set_override(false);
+    }
+
+    if cmd.get_flag("watch") {
+        if let Err(e) = parsing::watch_input(Path::new(&input_path), &thresholds, &options, &mut out) {
+            _exit_with_configuration_error(format!("cannot watch {}: {}", input_path, e));
+        }
+        return;
+    }
+
+    let outcome = if read_stdin {
+        let source_code = std::io::read_to_string(std::io::stdin()).unwrap_or_else(|e| {
+            eprintln!("Error reading standard input: {}", e);
+            std::process::exit(exit_code::PARSE_ERROR);
+        });
+        parsing::process_source(Path::new(&input_path), source_code, &thresholds, &options, &mut out)
+    } else {
+        parsing::process_input(&input_path, &thresholds, &options, &mut out)
+    };
+    if let Err(e) = out.flush() {
+        eprintln!("Error writing report: {}", e);
+    }
+
+    if cmd.get_flag("record-history") {
+        if outcome.parse_errors > 0 {
+            // Partial totals would show up as progress
+            eprintln!("Warning: not recording history, some files could not be parsed");
+        } else if let Err(e) = _record_history(Path::new(&input_path), &outcome.summary) {
+            eprintln!("Error recording history: {}", e);
+        }
+    }
+
+    let fail_on_score = cmd.get_one::<usize>("fail-on-score").copied();
+    let max_findings = cmd.get_one::<usize>("max-findings").copied();
+    if let Some(code) = _exit_code(&outcome, fail_on_score, max_findings) {
+        std::process::exit(code);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use crate::common::exit_code::exit_code;
+    use crate::fixtures::fixtures::ZERO_THRESHOLDS;
+    use crate::parsing::{process_source, AnalysisOptions, AnalysisOutcome, Ranking};
+    use super::{_exit_code, _parse_percent};
+
+    // Scores 8, 10, 8 and 1 against zero thresholds
+    const RANKED_FUNCTIONS: &str = "function a() { for (;;) {} }\n\
+                                    function b() { for (;;) { for (;;) {} } }\n\
+                                    function c() { for (;;) {} }\n\
+                                    function d() { return g(); }";
+
+    fn ranked_outcome(top: Option<usize>, top_percent: Option<f32>) -> AnalysisOutcome {
+        let options = AnalysisOptions { ranking: Some(Ranking { top, top_percent }), ..AnalysisOptions::default() };
+        process_source(Path::new("ranked.js"), RANKED_FUNCTIONS.to_string(), &ZERO_THRESHOLDS, &options, &mut Vec::new())
+    }
+
+    #[test]
+    fn top_percent_accepts_only_percentages() {
+        assert_eq!(_parse_percent("0"), Ok(0.0));
+        assert_eq!(_parse_percent("100"), Ok(100.0));
+        assert_eq!(_parse_percent("12.5"), Ok(12.5));
+        for argument in ["-1", "100.1", "ten", "NaN"] {
+            assert!(_parse_percent(argument).is_err(), "case {:?}", argument);
+        }
+    }
+
+    #[test]
+    fn exit_code_follows_the_limits() {
+        let outcome = |findings, max_score, parse_errors| AnalysisOutcome { findings, max_score, parse_errors, ..AnalysisOutcome::default() };
+        for (findings, max_score, fail_on_score, max_findings, expected) in [
+            (0, None, None, None, None),
+            (3, Some(9), None, None, None),
+            // --fail-on-score is inclusive, --max-findings is not
+            (3, Some(7), Some(7), None, Some(exit_code::FINDINGS_OVER_LIMIT)),
+            (3, Some(6), Some(7), None, None),
+            (3, Some(6), None, Some(3), None),
+            (4, Some(6), None, Some(3), Some(exit_code::FINDINGS_OVER_LIMIT)),
+            (0, None, Some(0), Some(0), None),
+            (1, Some(0), Some(0), None, Some(exit_code::FINDINGS_OVER_LIMIT)),
+        ] {
+            let case = (findings, max_score, fail_on_score, max_findings);
+            assert_eq!(_exit_code(&outcome(findings, max_score, 0), fail_on_score, max_findings), expected, "case {:?}", case);
+        }
+    }
+
+    #[test]
+    fn parse_errors_take_precedence_over_the_limits() {
+        let outcome = AnalysisOutcome { findings: 5, max_score: Some(10), parse_errors: 1, ..AnalysisOutcome::default() };
+        assert_eq!(_exit_code(&outcome, Some(1), Some(0)), Some(exit_code::PARSE_ERROR));
+        assert_eq!(_exit_code(&outcome, None, None), Some(exit_code::PARSE_ERROR));
+    }
+
+    #[test]
+    fn limits_apply_to_the_ranked_findings() {
+        let all = ranked_outcome(None, None);
+        assert_eq!((all.findings, all.max_score), (4, Some(10)));
+        assert_eq!(_exit_code(&all, None, Some(2)), Some(exit_code::FINDINGS_OVER_LIMIT));
+
+        // Only the reported findings count against --max-findings
+        let top = ranked_outcome(Some(2), None);
+        assert_eq!((top.findings, top.max_score), (2, Some(10)));
+        assert_eq!(_exit_code(&top, None, Some(2)), None);
+        // The strongest finding is always kept, so --fail-on-score still fails
+        assert_eq!(_exit_code(&top, Some(9), None), Some(exit_code::FINDINGS_OVER_LIMIT));
+
+        // An empty selection passes every limit
+        let none = ranked_outcome(None, Some(10.0));
+        assert_eq!((none.findings, none.max_score), (0, None));
+        assert_eq!(_exit_code(&none, Some(0), Some(0)), None);
+    }
 }
@@ -0,0 +1,88 @@
+pub mod fingerprint {
+    use swc_common::{BytePos, Span};
+    use swc_common::input::StringInput;
+    use swc_ecma_ast::EsVersion;
+    use swc_ecma_parser::Syntax;
+    use swc_ecma_parser::lexer::Lexer;
+
+    const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const FNV_PRIME: u64 = 0x100000001b3;
+
+    /// Hashes the tokens of the function at `span`, so that reformatting or commenting
+    /// the function keeps its hash. FNV-1a is used as it is stable across Rust releases.
+    pub fn body_hash(source_code: &str, file_start: BytePos, span: Span, syntax: Syntax) -> u64 {
+        let start = (span.lo - file_start).0 as usize;
+        let end = (span.hi - file_start).0 as usize;
+        let Some(function_source) = source_code.get(start..end) else {
+            return FNV_OFFSET_BASIS;
+        };
+
+        let input = StringInput::new(function_source, span.lo, span.hi);
+        let mut hash = FNV_OFFSET_BASIS;
+        for token in Lexer::new(syntax, EsVersion::latest(), input, None) {
+            let token_start = (token.span.lo - span.lo).0 as usize;
+            let token_end = (token.span.hi - span.lo).0 as usize;
+            let text = function_source.get(token_start..token_end).unwrap_or_default();
+            // Separator keeps `a b` and `ab` apart
+            for byte in text.bytes().chain([0]) {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+        }
+        hash
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use swc_common::{BytePos, Spanned};
+        use swc_ecma_parser::{Syntax, TsConfig};
+        use crate::fixtures::fixtures::parse_at;
+        use super::body_hash;
+
+        /// Hash of the first item of a file starting at `file_start`, like later files of a source map.
+        fn hash_at(source: &str, file_start: u32, syntax: Syntax) -> u64 {
+            let start = BytePos(file_start);
+            let module = parse_at(source, syntax, start, None);
+            body_hash(source, start, module.body[0].span(), syntax)
+        }
+
+        fn hash_of(source: &str) -> u64 {
+            hash_at(source, 1, Syntax::default())
+        }
+
+        #[test]
+        fn formatting_and_comments_keep_the_hash() {
+            let compact = hash_of("function mix(a, b) { return a * b; }");
+            let formatted = hash_of("function mix(a,b){\n    // product\n    return a*b; /* done */\n}");
+            assert_eq!(compact, formatted);
+        }
+
+        #[test]
+        fn position_in_the_file_keeps_the_hash() {
+            let source = "function mix(a, b) { return a * b; }";
+            assert_eq!(hash_at(source, 1, Syntax::default()), hash_at(source, 500, Syntax::default()));
+            assert_eq!(hash_of(source), hash_of(&format!("{}\n", source)));
+        }
+
+        #[test]
+        fn token_changes_change_the_hash() {
+            let original = hash_of("function mix(a, b) { return a * b; }");
+            assert_ne!(original, hash_of("function mix(a, b) { return a + b; }"));
+            assert_ne!(original, hash_of("function blend(a, b) { return a * b; }"));
+        }
+
+        #[test]
+        fn adjacent_tokens_are_kept_apart() {
+            assert_ne!(hash_of("let ab = 1;"), hash_of("let a = b1;"));
+        }
+
+        #[test]
+        fn typescript_annotations_are_hashed() {
+            let syntax = Syntax::Typescript(TsConfig::default());
+            assert_ne!(
+                hash_at("function mix(a: number) { return a; }", 1, syntax),
+                hash_at("function mix(a: string) { return a; }", 1, syntax),
+            );
+        }
+    }
+}
@@ -1,3 +1,15 @@
+#[allow(clippy::module_inception)]
 pub mod parsing;
 pub use parsing::parsing::*;
 
+#[allow(clippy::module_inception)]
+mod module_resolution;
+#[allow(clippy::module_inception)]
+mod fingerprint;
+#[allow(clippy::module_inception)]
+mod script_blocks;
+pub use script_blocks::script_blocks::source_blocks;
+
+#[allow(clippy::module_inception)]
+mod watch;
+pub use watch::watch::watch_input;
@@ -0,0 +1,270 @@
+pub mod module_resolution {
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use serde_json::Value;
+
+    const EXTENSIONS: [&str; 2] = ["ts", "js"];
+
+    /// Resolves import specifiers to files, following relative paths and the
+    /// `baseUrl` / `paths` mapping of the nearest `tsconfig.json`.
+    pub struct ModuleResolver {
+        base_url: Option<PathBuf>,
+        paths: Vec<(String, Vec<String>)>,
+    }
+
+    impl ModuleResolver {
+        /// Looks for `tsconfig.json` in `root` and its ancestors.
+        pub fn new(root: &Path) -> Self {
+            let tsconfig = root.ancestors()
+                .map(|dir| dir.join("tsconfig.json"))
+                .find(|candidate| candidate.is_file());
+
+            let mut resolver = ModuleResolver { base_url: None, paths: Vec::new() };
+            if let Some(tsconfig_path) = tsconfig {
+                if let Err(e) = resolver.load_tsconfig(&tsconfig_path) {
+                    eprintln!("Error reading {}: {}", tsconfig_path.display(), e);
+                }
+            }
+            resolver
+        }
+
+        fn load_tsconfig(&mut self, tsconfig_path: &Path) -> Result<(), String> {
+            let text = fs::read_to_string(tsconfig_path).map_err(|e| e.to_string())?;
+            let tsconfig: Value = serde_json::from_str(&_strip_jsonc(&text)).map_err(|e| e.to_string())?;
+            let tsconfig_dir = tsconfig_path.parent().unwrap_or(Path::new("."));
+            let compiler_options = &tsconfig["compilerOptions"];
+
+            let base_url = compiler_options["baseUrl"].as_str().map(|base_url| tsconfig_dir.join(base_url));
+            if let Value::Object(paths) = &compiler_options["paths"] {
+                for (pattern, targets) in paths {
+                    let targets = targets.as_array()
+                        .map(|targets| targets.iter().filter_map(Value::as_str).map(str::to_string).collect())
+                        .unwrap_or_default();
+                    self.paths.push((pattern.clone(), targets));
+                }
+            }
+            // `paths` are relative to `baseUrl`, or to the tsconfig itself without one
+            self.base_url = base_url.or_else(|| (!self.paths.is_empty()).then(|| tsconfig_dir.to_path_buf()));
+            Ok(())
+        }
+
+        pub fn resolve(&self, importer: &Path, specifier: &str) -> Option<PathBuf> {
+            if specifier.starts_with("./") || specifier.starts_with("../") || specifier.starts_with('/') {
+                let importer_dir = importer.parent().unwrap_or(Path::new("."));
+                return _resolve_file(&importer_dir.join(specifier));
+            }
+
+            let base_url = self.base_url.as_ref()?;
+            for (pattern, targets) in &self.paths {
+                if let Some(wildcard) = _match_pattern(pattern, specifier) {
+                    let resolved = targets.iter()
+                        .find_map(|target| _resolve_file(&base_url.join(target.replacen('*', wildcard, 1))));
+                    if resolved.is_some() {
+                        return resolved;
+                    }
+                }
+            }
+            _resolve_file(&base_url.join(specifier))
+        }
+    }
+
+    /// Returns the part of `specifier` matched by the `*` of a `paths` pattern.
+    fn _match_pattern<'s>(pattern: &str, specifier: &'s str) -> Option<&'s str> {
+        match pattern.split_once('*') {
+            Some((prefix, suffix)) => specifier.strip_prefix(prefix)?.strip_suffix(suffix),
+            None => (pattern == specifier).then_some(""),
+        }
+    }
+
+    fn _resolve_file(path: &Path) -> Option<PathBuf> {
+        if path.is_file() {
+            return fs::canonicalize(path).ok();
+        }
+        // `./utils.js` is commonly written for a `utils.ts` source
+        let stem = path.with_extension("");
+        let file_name = path.file_name()?.to_string_lossy().to_string();
+        let candidates = EXTENSIONS.iter()
+            .map(|extension| PathBuf::from(format!("{}.{}", path.display(), extension)))
+            .chain(EXTENSIONS.iter().map(|extension| stem.with_extension(extension)).filter(|_| file_name.contains('.')))
+            .chain(EXTENSIONS.iter().map(|extension| path.join("index").with_extension(extension)));
+        for candidate in candidates {
+            if candidate.is_file() {
+                return fs::canonicalize(candidate).ok();
+            }
+        }
+        None
+    }
+
+    /// tsconfig.json allows comments and trailing commas, serde_json does not.
+    fn _strip_jsonc(text: &str) -> String {
+        let chars: Vec<char> = text.chars().collect();
+        let mut without_comments = Vec::with_capacity(chars.len());
+        let mut index = 0;
+        let mut in_string = false;
+
+        while index < chars.len() {
+            let c = chars[index];
+            let next = chars.get(index + 1).copied();
+            if in_string {
+                without_comments.push(c);
+                if c == '\\' {
+                    without_comments.extend(next);
+                    index += 1;
+                } else if c == '"' {
+                    in_string = false;
+                }
+            } else if c == '/' && next == Some('/') {
+                while index < chars.len() && chars[index] != '\n' {
+                    index += 1;
+                }
+                continue;
+            } else if c == '/' && next == Some('*') {
+                index += 2;
+                while index < chars.len() && !(chars[index] == '*' && chars.get(index + 1) == Some(&'/')) {
+                    index += 1;
+                }
+                index += 2;
+                continue;
+            } else {
+                in_string = c == '"';
+                without_comments.push(c);
+            }
+            index += 1;
+        }
+
+        // Drop commas followed only by whitespace and a closing bracket
+        let mut stripped = String::with_capacity(without_comments.len());
+        let mut in_string = false;
+        let mut escaped = false;
+        for (index, &c) in without_comments.iter().enumerate() {
+            if in_string {
+                in_string = escaped || c != '"';
+                escaped = !escaped && c == '\\';
+            } else if c == '"' {
+                in_string = true;
+            } else if c == ',' {
+                let next_significant = without_comments[index + 1..].iter().find(|next| !next.is_whitespace());
+                if matches!(next_significant, Some('}') | Some(']')) {
+                    continue;
+                }
+            }
+            stripped.push(c);
+        }
+        stripped
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::fs;
+        use std::path::Path;
+        use serde_json::{json, Value};
+        use crate::fixtures::fixtures::project;
+        use super::{ModuleResolver, _match_pattern, _resolve_file, _strip_jsonc};
+
+        fn parse_jsonc(text: &str) -> Value {
+            serde_json::from_str(&_strip_jsonc(text)).expect("Stripped JSONC is not JSON")
+        }
+
+        #[test]
+        fn jsonc_comments_are_stripped() {
+            let text = "{\n  // line comment\n  \"a\": 1, /* block\n comment */ \"b\": 2\n}";
+            assert_eq!(parse_jsonc(text), json!({ "a": 1, "b": 2 }));
+        }
+
+        #[test]
+        fn jsonc_trailing_commas_are_stripped() {
+            let text = "{ \"paths\": { \"@/*\": [\"src/*\",], }, }";
+            assert_eq!(parse_jsonc(text), json!({ "paths": { "@/*": ["src/*"] } }));
+        }
+
+        #[test]
+        fn jsonc_strings_keep_comment_markers_and_commas() {
+            let text = r#"{ "url": "http://host/*x*/", "list": ",}", "quote": "a\",]" }"#;
+            assert_eq!(parse_jsonc(text), json!({ "url": "http://host/*x*/", "list": ",}", "quote": "a\",]" }));
+        }
+
+        #[test]
+        fn wildcard_pattern_returns_the_matched_part() {
+            assert_eq!(_match_pattern("@app/*", "@app/utils/math"), Some("utils/math"));
+            assert_eq!(_match_pattern("*.js", "lib/math.js"), Some("lib/math"));
+            assert_eq!(_match_pattern("@app/*", "@other/math"), None);
+        }
+
+        #[test]
+        fn exact_pattern_matches_only_itself() {
+            assert_eq!(_match_pattern("config", "config"), Some(""));
+            assert_eq!(_match_pattern("config", "config/dev"), None);
+        }
+
+        #[test]
+        fn files_resolve_with_extensions_and_index() {
+            let root = project("resolve-file", &[
+                ("lib/math.ts", ""),
+                ("lib/legacy.js", ""),
+                ("lib/utils/index.js", ""),
+                ("lib/data.json", ""),
+            ]);
+            let lib = root.join("lib");
+
+            assert_eq!(_resolve_file(&lib.join("math")), Some(lib.join("math.ts")));
+            assert_eq!(_resolve_file(&lib.join("legacy")), Some(lib.join("legacy.js")));
+            // `.js` written for a TypeScript source
+            assert_eq!(_resolve_file(&lib.join("math.js")), Some(lib.join("math.ts")));
+            assert_eq!(_resolve_file(&lib.join("utils")), Some(lib.join("utils/index.js")));
+            assert_eq!(_resolve_file(&lib.join("data.json")), Some(lib.join("data.json")));
+            assert_eq!(_resolve_file(&lib.join("missing")), None);
+            let _ = fs::remove_dir_all(root);
+        }
+
+        #[test]
+        fn relative_specifiers_resolve_from_the_importer() {
+            let root = project("relative", &[("src/app/main.ts", ""), ("src/lib/math.ts", "")]);
+            let resolver = ModuleResolver::new(&root);
+            let importer = root.join("src/app/main.ts");
+
+            assert_eq!(resolver.resolve(&importer, "../lib/math"), Some(root.join("src/lib/math.ts")));
+            // Bare specifiers are packages without a tsconfig
+            assert_eq!(resolver.resolve(&importer, "lib/math"), None);
+            let _ = fs::remove_dir_all(root);
+        }
+
+        #[test]
+        fn tsconfig_paths_and_base_url_resolve_aliases() {
+            let tsconfig = r#"{
+                // Aliases used across the app
+                "compilerOptions": {
+                    "baseUrl": "src",
+                    "paths": {
+                        "@lib/*": ["missing/*", "lib/*"], /* first target has no file */
+                        "config": ["config/dev.ts"],
+                    },
+                },
+            }"#;
+            let root = project("tsconfig", &[
+                ("tsconfig.json", tsconfig),
+                ("src/app/main.ts", ""),
+                ("src/lib/math.ts", ""),
+                ("src/config/dev.ts", ""),
+                ("src/shared/index.ts", ""),
+            ]);
+            let resolver = ModuleResolver::new(&root.join("src/app"));
+            let importer = root.join("src/app/main.ts");
+
+            assert_eq!(resolver.resolve(&importer, "@lib/math"), Some(root.join("src/lib/math.ts")));
+            assert_eq!(resolver.resolve(&importer, "config"), Some(root.join("src/config/dev.ts")));
+            assert_eq!(resolver.resolve(&importer, "shared"), Some(root.join("src/shared/index.ts")));
+            assert_eq!(resolver.resolve(&importer, "@lib/missing"), None);
+            let _ = fs::remove_dir_all(root);
+        }
+
+        #[test]
+        fn paths_without_base_url_are_relative_to_the_tsconfig() {
+            let tsconfig = r#"{ "compilerOptions": { "paths": { "~/*": ["./src/*"] } } }"#;
+            let root = project("paths-only", &[("tsconfig.json", tsconfig), ("src/lib/math.ts", "")]);
+            let resolver = ModuleResolver::new(&root);
+
+            assert_eq!(resolver.resolve(Path::new("main.ts"), "~/lib/math"), Some(root.join("src/lib/math.ts")));
+            let _ = fs::remove_dir_all(root);
+        }
+    }
+}
@@ -1,64 +1,645 @@
 
 pub mod parsing {
-    use std::path::Path;
+    use std::collections::{BTreeMap, HashMap, HashSet};
+    use std::path::{Path, PathBuf};
     use std::fs;
+    use std::io::Write;
     use std::rc::Rc;
     use std::ffi::OsStr;
-    use swc_common::FileName;
+    use swc_common::{BytePos, FileName, Span, Spanned};
+    use swc_common::comments::SingleThreadedComments;
     use swc_common::input::StringInput;
     use swc_common::source_map::SourceMap;
-    use swc_ecma_parser::{Parser, Syntax};
+    use swc_ecma_ast::Program;
+    use swc_ecma_parser::Parser;
     use swc_ecma_visit::VisitWith;
     use crate::Metrics;
-    use crate::visitor::FunctionAnalysisVisitor;
+    use crate::common::ChangedFiles;
+    use crate::common::baseline::baseline::{Baseline, BaselineFinding};
+    use crate::parsing::fingerprint::fingerprint::body_hash;
+    use crate::parsing::module_resolution::module_resolution::ModuleResolver;
+    use crate::parsing::script_blocks::script_blocks::{embeds_scripts, source_blocks};
+    use crate::visitor::{
+        analyze_calls, candidate_score, collect_module_links, find_clusters, FunctionAnalysis, FunctionAnalysisVisitor, ModuleAnalysis,
+        ModuleLinks, NormalizationMode, NormalizationModel, Suppressions,
+    };
+    use crate::visitor::function_analysis::function_analysis::FunctionId;
+    use crate::visitor::report::{write_report, Aggregate, AnalysisReport, Finding, ReportFormat, Summary};
 
+    #[derive(Default)]
+    pub struct AnalysisOptions {
+        /// Also report suppression comments that did not hide any finding
+        pub report_unused_suppressions: bool,
+        /// Only findings that are new or worsened compared to it are reported
+        pub baseline: Option<Baseline>,
+        /// File to record the current findings in
+        pub write_baseline: Option<PathBuf>,
+        /// Only changed files are analyzed
+        pub changed_files: Option<ChangedFiles>,
+        /// Only functions intersecting a changed hunk are reported
+        pub changed_functions_only: bool,
+        pub normalization: NormalizationMode,
+        /// Findings are sorted by score and shown with their percentile in the project
+        pub ranking: Option<Ranking>,
+        pub format: ReportFormat,
+        /// Every analyzed function is written, not only the findings
+        pub all_functions: bool,
+        /// Only the aggregates per file and directory are written
+        pub summary_only: bool,
+    }
+
+    #[derive(Default)]
+    pub struct Ranking {
+        /// Only the N strongest findings are reported
+        pub top: Option<usize>,
+        /// Only findings among the P% strongest functions of the project are reported
+        pub top_percent: Option<f32>,
+    }
+
+    /// What a CI gate needs to know about the analysis.
+    #[derive(Default)]
+    pub struct AnalysisOutcome {
+        /// Reported findings, not counting suppressed or baseline ones
+        pub findings: usize,
+        pub max_score: Option<usize>,
+        /// Files that could not be read or parsed
+        pub parse_errors: usize,
+        /// Totals over every analyzed function
+        pub summary: Aggregate,
+    }
+
+    /// Parsed files of the analyzed path, kept between analyses so `--watch` re-parses only what changed.
+    pub struct Project {
+        pub root: PathBuf,
+        // All files share one SourceMap so spans stay unique across the project. It only grows,
+        // re-parsed files leave their previous version behind until the project is parsed anew
+        pub source_map: Rc<SourceMap>,
+        parsed: BTreeMap<PathBuf, Result<ModuleAnalysis, String>>,
+    }
+
+    impl Project {
+        pub fn load(path: &Path, options: &AnalysisOptions) -> Self {
+            let root = if path == Path::new(".") {
+                std::env::current_dir().expect("Failed to get current directory")
+            } else {
+                path.to_path_buf()
+            };
+
+            let mut project = Project { root, source_map: Rc::new(SourceMap::default()), parsed: BTreeMap::new() };
+            project.update(&[project.root.clone()], options);
+            project
+        }
+
+        /// A single source text, like an unsaved editor buffer, analyzed as the file at `path`.
+        pub fn from_source(path: &Path, source_code: String) -> Self {
+            let mut project = Project { root: path.to_path_buf(), source_map: Rc::new(SourceMap::default()), parsed: BTreeMap::new() };
+            let module = _process_source(path, source_code, &project.source_map);
+            project.parsed.insert(path.to_path_buf(), module);
+            project
+        }
+
+        /// Parses the files at or below `paths` again and forgets the ones that are gone,
+        /// returns how many files changed.
+        pub fn update(&mut self, paths: &[PathBuf], options: &AnalysisOptions) -> usize {
+            let mut changed = 0;
+            for path in paths {
+                let mut files = Vec::new();
+                _collect_files(path, &mut files);
+                if let Some(changed_files) = &options.changed_files {
+                    files.retain(|file| changed_files.contains(file));
+                }
+
+                let removed: Vec<PathBuf> = self.parsed.keys()
+                    .filter(|file| file.starts_with(path) && !files.contains(file))
+                    .cloned()
+                    .collect();
+                for file in removed {
+                    self.parsed.remove(&file);
+                    changed += 1;
+                }
+                for file in files {
+                    let module = _process_file(&file, &self.source_map);
+                    self.parsed.insert(file, module);
+                    changed += 1;
+                }
+            }
+
+            // Bounds memory and the BytePos space of the SourceMap to twice the size of the project
+            let (live_bytes, stale_bytes) = self.source_map_usage();
+            if stale_bytes > live_bytes {
+                self.reparse_all();
+            }
+            changed
+        }
+
+        /// Bytes of the SourceMap taken by the current version of the parsed files, and by
+        /// versions replaced or removed since.
+        fn source_map_usage(&self) -> (usize, usize) {
+            let mut current = HashSet::new();
+            let (mut live_bytes, mut stale_bytes) = (0, 0);
+            for file in self.source_map.files().iter().rev() {
+                // Files are never moved in the SourceMap, the last one of a name is its current version
+                let size = (file.end_pos - file.start_pos).0 as usize + 1;
+                let is_current = match &file.name {
+                    FileName::Real(path) => self.parsed.contains_key(path) && current.insert(path.clone()),
+                    _ => false,
+                };
+                if is_current {
+                    live_bytes += size;
+                } else {
+                    stale_bytes += size;
+                }
+            }
+            (live_bytes, stale_bytes)
+        }
+
+        fn reparse_all(&mut self) {
+            self.source_map = Rc::new(SourceMap::default());
+            let files: Vec<PathBuf> = self.parsed.keys().cloned().collect();
+            for file in files {
+                let module = _process_file(&file, &self.source_map);
+                self.parsed.insert(file, module);
+            }
+        }
+
+        /// Errors of the files that could not be read or parsed, with their location.
+        pub fn parse_errors(&self) -> impl Iterator<Item = &String> {
+            self.parsed.values().filter_map(|module| module.as_ref().err())
+        }
+
+        /// Errors of the files at or below `paths`.
+        pub fn parse_errors_below<'p>(&'p self, paths: &'p [PathBuf]) -> impl Iterator<Item = &'p String> {
+            self.parsed.iter()
+                .filter(|(file, _)| paths.iter().any(|path| file.starts_with(path)))
+                .filter_map(|(_, module)| module.as_ref().err())
+        }
+
+        /// Modules of the parsed files, with calls followed across files.
+        pub fn modules(&self) -> Vec<ModuleAnalysis> {
+            let mut modules: Vec<ModuleAnalysis> = self.parsed.values()
+                .filter_map(|module| module.as_ref().ok())
+                .cloned()
+                .collect();
+            _link_modules(&mut modules, &self.root);
+            analyze_calls(&mut modules);
+            modules
+        }
+    }
+
+    /// Which candidates of an analysis are reported.
+    pub struct Selection {
+        pub model: NormalizationModel,
+        /// Functions over the thresholds and not suppressed, with their score
+        pub candidates: Vec<(FunctionId, usize)>,
+        /// The candidates as recorded in a baseline
+        pub baseline_findings: Vec<BaselineFinding>,
+        /// Whether each candidate is reported, false for the ones known to the baseline
+        pub reported: Vec<bool>,
+        /// Reported candidates, ranked when requested
+        pub findings: Vec<(FunctionId, usize)>,
+    }
+
+    pub fn select_findings(
+        modules: &[ModuleAnalysis],
+        source_map: &SourceMap,
+        thresholds: &Metrics,
+        options: &AnalysisOptions,
+    ) -> Selection {
+        let model = NormalizationModel::new(
+            options.normalization,
+            thresholds,
+            modules.iter().flat_map(|module| &module.functions),
+        );
 
-    pub fn process_input<P: AsRef<Path>>(path: P, thresholds: &Metrics) {
-        let path = path.as_ref();
+        let mut candidates = Vec::new();
+        for (module_index, module) in modules.iter().enumerate() {
+            for (function_index, function) in module.functions.iter().enumerate() {
+                let Some(score) = candidate_score(function, thresholds, &model) else {
+                    continue;
+                };
+                if options.changed_functions_only && !_is_function_changed(module, function.span, options, source_map) {
+                    continue;
+                }
+                if !module.suppressions.suppresses(function.span, source_map) {
+                    candidates.push((FunctionId { module: module_index, function: function_index }, score));
+                }
+            }
+        }
 
-        if path == Path::new(".") {
-            let current_dir = std::env::current_dir().expect("Failed to get current directory");
-            process_input(&current_dir, &thresholds);
-        } else if path.is_file() {
-            if path.extension().map_or(false, |ext| ext == "js" || ext == "ts") {
-                _process_file(path, &thresholds);
+        let baseline_findings: Vec<BaselineFinding> = candidates.iter()
+            .map(|&(id, score)| _baseline_finding(modules, id, score))
+            .collect();
+        let reported = match &options.baseline {
+            Some(baseline) => {
+                let same_model = baseline.normalization.as_deref() == Some(model.id().as_str());
+                if !same_model {
+                    eprintln!(
+                        "Warning: the baseline was recorded with normalization {}, not {}, only new findings are reported",
+                        baseline.normalization.as_deref().unwrap_or("unknown"),
+                        model.id()
+                    );
+                }
+                baseline.new_or_worsened(&baseline_findings, same_model)
+            }
+            None => vec![true; baseline_findings.len()],
+        };
+
+        let mut findings: Vec<(FunctionId, usize)> = candidates.iter().zip(&reported)
+            .filter(|(_, &reported)| reported)
+            .map(|(&candidate, _)| candidate)
+            .collect();
+        if let Some(ranking) = &options.ranking {
+            _rank_findings(&mut findings, ranking, modules, &model);
+        }
+
+        Selection { model, candidates, baseline_findings, reported, findings }
+    }
+
+    /// Analyzes the file or directory and writes the report in the requested format to `out`.
+    pub fn process_input<P: AsRef<Path>>(
+        path: P,
+        thresholds: &Metrics,
+        options: &AnalysisOptions,
+        out: &mut dyn Write,
+    ) -> AnalysisOutcome {
+        let project = Project::load(path.as_ref(), options);
+        report_project(&project, thresholds, options, out)
+    }
+
+    /// Analyzes source text that has no file of its own, like standard input, as the file at `path`
+    /// (its extension picks the syntax) and writes the report to `out`.
+    pub fn process_source(
+        path: &Path,
+        source_code: String,
+        thresholds: &Metrics,
+        options: &AnalysisOptions,
+        out: &mut dyn Write,
+    ) -> AnalysisOutcome {
+        let project = Project::from_source(path, source_code);
+        report_project(&project, thresholds, options, out)
+    }
+
+    /// Analyzes the parsed files of the project and writes the report.
+    pub fn report_project(project: &Project, thresholds: &Metrics, options: &AnalysisOptions, out: &mut dyn Write) -> AnalysisOutcome {
+        let mut outcome = AnalysisOutcome::default();
+        for e in project.parse_errors() {
+            eprintln!("Error parsing {}", e);
+            outcome.parse_errors += 1;
+        }
+
+        let modules = project.modules();
+        let source_map = &project.source_map;
+        let root = &project.root;
+        let Selection { model, candidates, baseline_findings, reported, findings: reported_findings } =
+            select_findings(&modules, source_map, thresholds, options);
+
+        let findings_report: Vec<Finding> = reported_findings.iter()
+            .map(|&(id, score)| {
+                let function = ModuleAnalysis::function(&modules, id);
+                _finding(id, function, score, &model, options)
+            })
+            .collect();
+        let all_scores: Vec<(FunctionId, usize)> = modules.iter().enumerate()
+            .flat_map(|(module_index, module)| module.functions.iter().enumerate().map(move |(function_index, function)| {
+                (FunctionId { module: module_index, function: function_index }, function)
+            }))
+            .map(|(id, function)| (id, model.score(function)))
+            .collect();
+        let functions = options.all_functions.then(|| {
+            all_scores.iter()
+                .map(|&(id, score)| _finding(id, ModuleAnalysis::function(&modules, id), score, &model, options))
+                .collect()
+        });
+        outcome.findings = findings_report.len();
+        outcome.max_score = findings_report.iter().map(|finding| finding.score).max();
+
+        // Candidates calling each other are cheaper to port together
+        let scores: HashMap<FunctionId, usize> = candidates.iter().copied().collect();
+        let reported_ids: Vec<FunctionId> = findings_report.iter().map(|finding| finding.id).collect();
+        let clusters = find_clusters(&modules, &scores).into_iter()
+            .filter(|cluster| cluster.candidates.iter().any(|id| reported_ids.contains(id)))
+            .collect();
+
+        let unused_suppressions = if options.report_unused_suppressions {
+            modules.iter().flat_map(|module| module.suppressions.unused()).collect()
+        } else {
+            Vec::new()
+        };
+
+        // Paths of a single analyzed file, possibly one read from stdin, are relative to its directory
+        let summary_root = if root.is_dir() { root.as_path() } else { root.parent().unwrap_or(root) };
+        let summary = Summary::collect(&modules, &all_scores, &reported_ids, summary_root);
+
+        outcome.summary = summary.total.clone();
+
+        let report = AnalysisReport {
+            modules: &modules,
+            thresholds,
+            findings: findings_report,
+            functions,
+            clusters,
+            unused_suppressions,
+            known_findings: options.baseline.as_ref().map(|_| reported.iter().filter(|&&reported| !reported).count()),
+            summary,
+            summary_only: options.summary_only,
+        };
+        if let Err(e) = write_report(&report, options.format, source_map, out) {
+            eprintln!("Error writing report: {}", e);
+        }
+
+        if let Some(baseline_path) = &options.write_baseline {
+            match Baseline::new(baseline_findings, model.id()).store(baseline_path) {
+                Ok(()) => eprintln!("Baseline with {} findings written to {}", candidates.len(), baseline_path.display()),
+                Err(e) => eprintln!("Error writing baseline: {}", e),
+            }
+        }
+
+        outcome
+    }
+
+    /// Sorts the strongest findings first and drops the ones outside of the requested top.
+    fn _rank_findings(findings: &mut Vec<(FunctionId, usize)>, ranking: &Ranking, modules: &[ModuleAnalysis], model: &NormalizationModel) {
+        let raw_score = |id: FunctionId| model.raw_score(ModuleAnalysis::function(modules, id));
+        findings.sort_by(|&(a, a_score), &(b, b_score)| {
+            b_score.cmp(&a_score).then(raw_score(b).total_cmp(&raw_score(a))).then(a.cmp(&b))
+        });
+        if let Some(top_percent) = ranking.top_percent {
+            findings.retain(|&(id, _)| model.rank(ModuleAnalysis::function(modules, id)).top_share <= top_percent);
+        }
+        if let Some(top) = ranking.top {
+            findings.truncate(top);
+        }
+    }
+
+    fn _finding<'a>(
+        id: FunctionId,
+        function: &'a FunctionAnalysis,
+        score: usize,
+        model: &NormalizationModel,
+        options: &AnalysisOptions,
+    ) -> Finding<'a> {
+        Finding {
+            id,
+            function,
+            score,
+            raw_score: model.raw_score(function),
+            rank: options.ranking.as_ref().map(|_| model.rank(function)),
+        }
+    }
+
+    fn _is_function_changed(module: &ModuleAnalysis, span: Span, options: &AnalysisOptions, source_map: &SourceMap) -> bool {
+        let start = source_map.lookup_char_pos(span.lo).line;
+        let end = source_map.lookup_char_pos(span.hi).line;
+        options.changed_files.as_ref().is_none_or(|changed_files| changed_files.touches(&module.path, start, end))
+    }
+
+    fn _baseline_finding(modules: &[ModuleAnalysis], id: FunctionId, score: usize) -> BaselineFinding {
+        let path = &modules[id.module].path;
+        // Relative to the working directory, so the baseline does not depend on the checkout location
+        let file = fs::canonicalize(path).ok()
+            .and_then(|path| {
+                let cwd = std::env::current_dir().ok().and_then(|cwd| fs::canonicalize(cwd).ok())?;
+                path.strip_prefix(cwd).ok().map(Path::to_path_buf)
+            })
+            .unwrap_or(path.clone());
+        let function = ModuleAnalysis::function(modules, id);
+
+        BaselineFinding {
+            file: file.to_string_lossy().replace('\\', "/"),
+            function: function.qualified_name.clone(),
+            body_hash: format!("{:016x}", function.body_hash),
+            score,
+        }
+    }
+
+    fn _collect_files(path: &Path, files: &mut Vec<PathBuf>) {
+        if path.is_file() {
+            if path.extension().and_then(OsStr::to_str).is_some_and(|ext| ext == "js" || ext == "ts" || embeds_scripts(ext)) {
+                files.push(path.to_path_buf());
             }
         } else if path.is_dir() {
-            for entry in fs::read_dir(path).expect("Failed to read directory") {
-                let entry = entry.expect("Failed to read directory entry");
-                process_input(entry.path(), thresholds);
+            // Directories can vanish between the check and the read, like during `git checkout`,
+            // their files are then left out as removed
+            let entries = match fs::read_dir(path) {
+                Ok(entries) => entries,
+                Err(e) => return _skip_unreadable(path, e),
+            };
+            for entry in entries {
+                match entry {
+                    Ok(entry) => _collect_files(&entry.path(), files),
+                    Err(e) => _skip_unreadable(path, e),
+                }
             }
         }
     }
 
-    fn _process_file<P: AsRef<Path>>(file_path: P, thresholds: &Metrics) {
-        let source_code = fs::read_to_string(&file_path).expect("Failed to read file");
+    fn _skip_unreadable(path: &Path, e: std::io::Error) {
+        if e.kind() != std::io::ErrorKind::NotFound {
+            eprintln!("Error reading {}: {}", path.display(), e);
+        }
+    }
 
-        let source_map: Rc<SourceMap> = Rc::new(SourceMap::default());
+    /// Resolves the import and export specifiers of every module to the analyzed files they point at.
+    fn _link_modules(modules: &mut [ModuleAnalysis], root: &Path) {
+        let resolver = ModuleResolver::new(&fs::canonicalize(root).unwrap_or(root.to_path_buf()));
+        let index_by_path: HashMap<PathBuf, usize> = modules.iter()
+            .enumerate()
+            .filter_map(|(index, module)| fs::canonicalize(&module.path).ok().map(|path| (path, index)))
+            .collect();
 
+        for module in modules.iter_mut() {
+            let importer = fs::canonicalize(&module.path).unwrap_or(module.path.clone());
+            for source in module.links.sources_mut() {
+                source.resolved = resolver.resolve(&importer, &source.specifier)
+                    .and_then(|path| index_by_path.get(&path).copied());
+            }
+        }
+    }
+
+    fn _process_file(file_path: &Path, source_map: &Rc<SourceMap>) -> Result<ModuleAnalysis, String> {
+        let source_code = fs::read_to_string(file_path)
+            .map_err(|e| format!("{}: {}", file_path.display(), e))?;
+        _process_source(file_path, source_code, source_map)
+    }
+
+    fn _process_source(file_path: &Path, source_code: String, source_map: &Rc<SourceMap>) -> Result<ModuleAnalysis, String> {
         // Register the file with the SourceMap
         let file = source_map.new_source_file(
-            FileName::Real(file_path.as_ref().to_path_buf()),
+            FileName::Real(file_path.to_path_buf()),
             source_code.clone()
         );
 
-        // Create an input from the source code
-        let input = StringInput::new(&source_code, file.start_pos, file.end_pos);
+        // Components and pages are parsed block by block, their spans point into the whole file
+        let file_extension = file_path.extension().and_then(OsStr::to_str).unwrap_or_default();
+        let blocks = source_blocks(&source_code, file_extension);
 
-        let file_extension = file_path.as_ref().extension().and_then(OsStr::to_str);
-        let lang_syntax = if file_extension == Some("ts") {
-            Syntax::Typescript(Default::default())
-        } else {
-            Syntax::default()
-        };
+        // Keep comments for the suppression directives
+        let comments = SingleThreadedComments::default();
+        let mut visitor = FunctionAnalysisVisitor::new();
+        let mut links = ModuleLinks::default();
+        for block in blocks {
+            // Create an input from the code of the block
+            let input = StringInput::new(
+                &source_code[block.range.clone()],
+                file.start_pos + BytePos(block.range.start as u32),
+                file.start_pos + BytePos(block.range.end as u32),
+            );
+            let mut parser = Parser::new(block.syntax, input, Some(&comments));
 
-        // Create a parser
-        let mut parser = Parser::new(lang_syntax, input, None);
+            // Parse the source code into an AST
+            let program = if block.module {
+                parser.parse_module().map(Program::Module)
+            } else {
+                parser.parse_script().map(Program::Script)
+            };
+            let program = program.map_err(|e| {
+                let location = source_map.lookup_char_pos(e.span().lo);
+                format!("{}:{}:{}: {}", file_path.display(), location.line, location.col_display, e.kind().msg())
+            })?;
 
-        // Parse the source code into an AST
-        let module = parser.parse_module().expect("Failed to parse module");
+            let block_functions = visitor.functions.len();
+            program.visit_with(&mut visitor);
+            for function in &mut visitor.functions[block_functions..] {
+                function.body_hash = body_hash(&source_code, file.start_pos, function.span, block.syntax);
+            }
+            // Classic scripts share globals, they neither import nor export
+            if let Program::Module(module) = &program {
+                let block_links = collect_module_links(module);
+                links.imports.extend(block_links.imports);
+                links.exports.extend(block_links.exports);
+            }
+        }
+
+        Ok(ModuleAnalysis {
+            path: file_path.to_path_buf(),
+            functions: visitor.functions,
+            links,
+            suppressions: Suppressions::collect(&comments, source_map),
+        })
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::fs;
+        use std::path::Path;
+        use crate::common::baseline::baseline::Baseline;
+        use crate::fixtures::fixtures::{project, ZERO_THRESHOLDS};
+        use crate::visitor::NormalizationMode;
+        use super::{select_findings, AnalysisOptions, Project, Ranking};
+
+        // Raw scores 3, 6, 3 and 0.5 against zero thresholds, so scores 8, 10, 8 and 1
+        const RANKED_FUNCTIONS: &str = "function a() { for (;;) {} }\n\
+                                        function b() { for (;;) { for (;;) {} } }\n\
+                                        function c() { for (;;) {} }\n\
+                                        function d() { return g(); }";
+
+        fn ranked(top: Option<usize>, top_percent: Option<f32>) -> Vec<(String, usize)> {
+            let project = Project::from_source(Path::new("ranked.js"), RANKED_FUNCTIONS.to_string());
+            let modules = project.modules();
+            let options = AnalysisOptions { ranking: Some(Ranking { top, top_percent }), ..AnalysisOptions::default() };
+            select_findings(&modules, &project.source_map, &ZERO_THRESHOLDS, &options).findings.iter()
+                .map(|&(id, score)| (modules[id.module].functions[id.function].display_name().to_string(), score))
+                .collect()
+        }
+
+        fn names(findings: &[(String, usize)]) -> Vec<&str> {
+            findings.iter().map(|(name, _)| name.as_str()).collect()
+        }
+
+        #[test]
+        fn repeated_updates_keep_the_source_map_bounded() {
+            let root = project("update-bounded", &[("a.js", "function a(x) { return x * 2; }"), ("b.js", "function b() {}")]);
+            let options = AnalysisOptions::default();
+            let mut analyzed = Project::load(&root, &options);
+            let changed = [root.join("a.js")];
+            for version in 0..50 {
+                fs::write(&changed[0], format!("function a(x) {{ return x * {}; }}", version)).expect("Failed to edit fixture");
+                assert_eq!(analyzed.update(&changed, &options), 1);
+                assert!(analyzed.source_map.files().len() <= 4, "{} files after {} updates", analyzed.source_map.files().len(), version + 1);
+            }
+
+            // Spans point into the current version of every file
+            let modules = analyzed.modules();
+            let snippets: Vec<String> = modules.iter()
+                .map(|module| {
+                    let span = module.functions[0].span;
+                    let (start, end) = (analyzed.source_map.lookup_byte_offset(span.lo), analyzed.source_map.lookup_byte_offset(span.hi));
+                    start.sf.src[start.pos.0 as usize..end.pos.0 as usize].to_string()
+                })
+                .collect();
+            assert_eq!(snippets, vec!["function a(x) { return x * 49; }", "function b() {}"]);
+            let _ = fs::remove_dir_all(root);
+        }
+
+        #[test]
+        fn parse_errors_below_lists_only_the_given_paths() {
+            let root = project("parse-errors", &[("broken.js", "function ("), ("lib/bad.js", "let = ;"), ("lib/ok.js", "let a = 1;")]);
+            let analyzed = Project::load(&root, &AnalysisOptions::default());
+            assert_eq!(analyzed.parse_errors().count(), 2);
 
-        let mut visitor = FunctionAnalysisVisitor::new(thresholds, source_map);
-        module.visit_with(&mut visitor);
+            let changed = [root.join("lib")];
+            let errors: Vec<&String> = analyzed.parse_errors_below(&changed).collect();
+            assert_eq!(errors.len(), 1);
+            assert!(errors[0].contains("bad.js"), "{}", errors[0]);
+            let _ = fs::remove_dir_all(root);
+        }
+
+        #[test]
+        fn baseline_scores_are_compared_only_for_the_same_model_version() {
+            let project = Project::from_source(Path::new("mix.js"), "function mix(a) { for (;;) { for (;;) {} } }".to_string());
+            let modules = project.modules();
+            let options = AnalysisOptions { normalization: NormalizationMode::ThresholdRelative, ..AnalysisOptions::default() };
+            let selection = select_findings(&modules, &project.source_map, &ZERO_THRESHOLDS, &options);
+            assert_eq!(selection.model.id(), "threshold-relative@1");
+            assert_eq!(selection.findings.len(), 1);
+
+            // The recorded finding scored lower, so it worsened if the scores are comparable
+            let mut recorded = selection.baseline_findings.clone();
+            recorded[0].score -= 1;
+            for (normalization, reported) in [("threshold-relative@1", 1), ("threshold-relative@0", 0), ("absolute@1", 0)] {
+                let options = AnalysisOptions {
+                    normalization: NormalizationMode::ThresholdRelative,
+                    baseline: Some(Baseline::new(recorded.clone(), normalization.to_string())),
+                    ..AnalysisOptions::default()
+                };
+                let selection = select_findings(&modules, &project.source_map, &ZERO_THRESHOLDS, &options);
+                assert_eq!(selection.findings.len(), reported, "case {:?}", normalization);
+            }
+        }
+
+        #[test]
+        fn ranking_sorts_by_score_and_keeps_source_order_for_ties() {
+            let findings = ranked(None, None);
+            assert_eq!(findings, vec![("b".to_string(), 10), ("a".to_string(), 8), ("c".to_string(), 8), ("d".to_string(), 1)]);
+        }
+
+        #[test]
+        fn top_cuts_between_tied_findings() {
+            assert_eq!(names(&ranked(Some(2), None)), vec!["b", "a"]);
+            assert_eq!(names(&ranked(Some(0), None)), Vec::<&str>::new());
+            assert_eq!(names(&ranked(Some(10), None)), vec!["b", "a", "c", "d"]);
+        }
+
+        #[test]
+        fn top_percent_keeps_or_drops_tied_findings_together() {
+            // b is in the top 25% of the project, a and c share the top 75%
+            for (top_percent, expected) in [
+                (0.0, vec![]),
+                (10.0, vec![]),
+                (25.0, vec!["b"]),
+                (50.0, vec!["b"]),
+                (74.9, vec!["b"]),
+                (75.0, vec!["b", "a", "c"]),
+                (100.0, vec!["b", "a", "c", "d"]),
+            ] {
+                assert_eq!(names(&ranked(None, Some(top_percent))), expected, "case {:?}", top_percent);
+            }
+        }
+
+        #[test]
+        fn top_applies_after_top_percent() {
+            assert_eq!(names(&ranked(Some(2), Some(75.0))), vec!["b", "a"]);
+            assert_eq!(names(&ranked(Some(2), Some(25.0))), vec!["b"]);
+        }
     }
 }
@@ -0,0 +1,324 @@
+pub mod script_blocks {
+    use std::ops::Range;
+    use swc_ecma_parser::{EsConfig, Syntax, TsConfig};
+
+    /// Components keeping their code in `<script>` blocks next to the markup.
+    pub const COMPONENT_EXTENSIONS: [&str; 3] = ["vue", "svelte", "astro"];
+    pub const HTML_EXTENSIONS: [&str; 2] = ["html", "htm"];
+
+    // `type` values of classic scripts, other than `module` the browser does not run the rest
+    const JAVASCRIPT_TYPES: [&str; 6] = [
+        "", "text/javascript", "application/javascript", "text/ecmascript", "application/ecmascript", "application/x-javascript",
+    ];
+
+    /// Code embedded in markup, as a byte range of the file source.
+    pub struct ScriptBlock {
+        pub range: Range<usize>,
+        pub syntax: Syntax,
+        /// Parsed as an ES module, else as a classic script
+        pub module: bool,
+    }
+
+    /// Whether files with the extension are markup with embedded scripts.
+    pub fn embeds_scripts(extension: &str) -> bool {
+        COMPONENT_EXTENSIONS.contains(&extension) || HTML_EXTENSIONS.contains(&extension)
+    }
+
+    /// Code of a source file as parsed: the script blocks of markup, else the whole file
+    /// with the syntax of its extension.
+    pub fn source_blocks(source: &str, extension: &str) -> Vec<ScriptBlock> {
+        if embeds_scripts(extension) {
+            return extract_script_blocks(source, extension);
+        }
+        let syntax = match extension {
+            "ts" | "mts" | "cts" => _syntax(Some("ts"), false),
+            "tsx" | "jsx" => _syntax(Some(extension), false),
+            _ => Syntax::default(),
+        };
+        vec![ScriptBlock { range: 0..source.len(), syntax, module: true }]
+    }
+
+    /// Inline `<script>` elements of a Vue, Svelte or Astro component or an HTML page, and the frontmatter
+    /// of Astro components. Scripts loaded with `src`, scripts in HTML comments and, in pages,
+    /// data blocks like `type="application/json"` are skipped.
+    pub fn extract_script_blocks(source: &str, extension: &str) -> Vec<ScriptBlock> {
+        let html = HTML_EXTENSIONS.contains(&extension);
+        // Astro compiles both its frontmatter and its scripts as TypeScript
+        let typescript_by_default = extension == "astro";
+        let mut blocks = Vec::new();
+        let mut position = 0;
+        if extension == "astro" {
+            if let Some((range, end)) = _frontmatter(source) {
+                blocks.push(ScriptBlock { range, syntax: _syntax(None, true), module: true });
+                position = end;
+            }
+        }
+
+        while let Some(offset) = source[position..].find('<') {
+            let start = position + offset;
+            if source[start..].starts_with("<!--") {
+                position = source[start..].find("-->").map(|length| start + length + 3).unwrap_or(source.len());
+                continue;
+            }
+            let Some((attributes, content_start)) = _script_tag(source, start) else {
+                position = start + 1;
+                continue;
+            };
+            if attributes.trim_end().ends_with('/') {
+                position = content_start;
+                continue;
+            }
+
+            let content_end = source[content_start..].to_ascii_lowercase().find("</script")
+                .map(|length| content_start + length)
+                .unwrap_or(source.len());
+            let attributes = _attributes(attributes);
+            let attribute = |name: &str| attributes.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_deref());
+            if html {
+                // Pages only run JavaScript, `lang` means nothing to browsers
+                let script_type = attribute("type").flatten().unwrap_or_default().trim().to_ascii_lowercase();
+                let module = script_type == "module";
+                if attribute("src").is_none() && (module || JAVASCRIPT_TYPES.contains(&script_type.as_str())) {
+                    blocks.push(ScriptBlock { range: content_start..content_end, syntax: Syntax::default(), module });
+                }
+            } else if attribute("src").is_none() {
+                let lang = attribute("lang").flatten().map(str::to_ascii_lowercase);
+                blocks.push(ScriptBlock {
+                    range: content_start..content_end,
+                    syntax: _syntax(lang.as_deref(), typescript_by_default),
+                    module: true,
+                });
+            }
+            position = content_end;
+        }
+        blocks
+    }
+
+    fn _syntax(lang: Option<&str>, typescript_by_default: bool) -> Syntax {
+        match lang {
+            Some("ts" | "typescript") => Syntax::Typescript(TsConfig::default()),
+            Some("tsx") => Syntax::Typescript(TsConfig { tsx: true, ..TsConfig::default() }),
+            Some("jsx") => Syntax::Es(EsConfig { jsx: true, ..EsConfig::default() }),
+            _ if typescript_by_default => Syntax::Typescript(TsConfig::default()),
+            _ => Syntax::default(),
+        }
+    }
+
+    /// Code between the `---` fences opening an Astro component, and where the closing fence ends.
+    fn _frontmatter(source: &str) -> Option<(Range<usize>, usize)> {
+        let fence_start = source.len() - source.trim_start().len();
+        if !source[fence_start..].starts_with("---") {
+            return None;
+        }
+        let content_start = source[fence_start..].find('\n').map(|length| fence_start + length + 1)?;
+
+        let mut line_start = content_start;
+        for line in source[content_start..].split_inclusive('\n') {
+            if line.trim_end() == "---" {
+                return Some((content_start..line_start, line_start + line.len()));
+            }
+            line_start += line.len();
+        }
+        None
+    }
+
+    /// Attributes of the `<script>` tag opening at `start` and where its content starts.
+    fn _script_tag(source: &str, start: usize) -> Option<(&str, usize)> {
+        let name_end = start + "<script".len();
+        let name = source.get(start + 1..name_end)?;
+        let after_name = source[name_end..].chars().next()?;
+        if !name.eq_ignore_ascii_case("script") || !(after_name.is_whitespace() || after_name == '>' || after_name == '/') {
+            return None;
+        }
+
+        // `>` inside quoted attribute values does not close the tag
+        let mut quote: Option<char> = None;
+        for (offset, c) in source[name_end..].char_indices() {
+            match (quote, c) {
+                (Some(open), _) if c == open => quote = None,
+                (Some(_), _) => {}
+                (None, '"' | '\'') => quote = Some(c),
+                (None, '>') => return Some((&source[name_end..name_end + offset], name_end + offset + 1)),
+                (None, _) => {}
+            }
+        }
+        None
+    }
+
+    /// Lowercase names and unquoted values of the attributes of a tag, `None` for attributes without a value.
+    fn _attributes(text: &str) -> Vec<(String, Option<String>)> {
+        let mut attributes = Vec::new();
+        let mut rest = text.trim_start_matches(|c: char| c.is_whitespace() || c == '/');
+        while !rest.is_empty() {
+            let name_end = rest.find(|c: char| c.is_whitespace() || c == '=' || c == '/').unwrap_or(rest.len());
+            let name = rest[..name_end].to_ascii_lowercase();
+            rest = rest[name_end..].trim_start();
+
+            let mut value = None;
+            if let Some(after_equals) = rest.strip_prefix('=') {
+                let after_equals = after_equals.trim_start();
+                let (text, remaining) = match after_equals.chars().next() {
+                    Some(quote @ ('"' | '\'')) => {
+                        let end = after_equals[1..].find(quote).map(|length| length + 1).unwrap_or(after_equals.len());
+                        (&after_equals[1..end], after_equals.get(end + 1..).unwrap_or_default())
+                    }
+                    _ => {
+                        let end = after_equals.find(char::is_whitespace).unwrap_or(after_equals.len());
+                        (&after_equals[..end], &after_equals[end..])
+                    }
+                };
+                value = Some(text.to_string());
+                rest = remaining;
+            }
+            if !name.is_empty() {
+                attributes.push((name, value));
+            }
+            rest = rest.trim_start_matches(|c: char| c.is_whitespace() || c == '/');
+        }
+        attributes
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use swc_ecma_parser::{EsConfig, Syntax, TsConfig};
+        use super::{extract_script_blocks, source_blocks};
+
+        /// Code, syntax and module flag of each block.
+        fn blocks_of<'s>(source: &'s str, extension: &str) -> Vec<(&'s str, &'static str, bool)> {
+            source_blocks(source, extension).into_iter()
+                .map(|block| {
+                    let syntax = match block.syntax {
+                        Syntax::Typescript(TsConfig { tsx: true, .. }) => "tsx",
+                        Syntax::Typescript(_) => "ts",
+                        Syntax::Es(EsConfig { jsx: true, .. }) => "jsx",
+                        Syntax::Es(_) => "js",
+                    };
+                    (&source[block.range], syntax, block.module)
+                })
+                .collect()
+        }
+
+        #[test]
+        fn astro_frontmatter_and_scripts_are_typescript() {
+            let source = "---\nconst title = 'Chart';\n---\n<h1>{title}</h1>\n<script>\nlet count = 2;\n</script>\n";
+            assert_eq!(blocks_of(source, "astro"), vec![
+                ("const title = 'Chart';\n", "ts", true),
+                ("\nlet count = 2;\n", "ts", true),
+            ]);
+            let ranges: Vec<_> = extract_script_blocks(source, "astro").into_iter().map(|block| block.range).collect();
+            assert_eq!(ranges, vec![4..27, 56..72]);
+        }
+
+        #[test]
+        fn astro_frontmatter_may_follow_blank_lines() {
+            assert_eq!(blocks_of("\n  ---\nconst a = 1;\n---  \n<p></p>", "astro"), vec![("const a = 1;\n", "ts", true)]);
+        }
+
+        #[test]
+        fn unclosed_frontmatter_is_not_code() {
+            assert!(blocks_of("---\nconst a = 1;\n", "astro").is_empty());
+        }
+
+        #[test]
+        fn fences_outside_astro_are_markup() {
+            assert_eq!(blocks_of("---\n<script>let a;</script>", "vue"), vec![("let a;", "js", true)]);
+        }
+
+        #[test]
+        fn plain_files_are_one_block_in_the_syntax_of_their_extension() {
+            for (extension, expected) in [("js", "js"), ("mjs", "js"), ("jsx", "jsx"), ("ts", "ts"), ("mts", "ts"), ("cts", "ts"), ("tsx", "tsx")] {
+                assert_eq!(blocks_of("let a;", extension), vec![("let a;", expected, true)], "case {:?}", extension);
+            }
+        }
+
+        #[test]
+        fn lang_attribute_picks_the_syntax() {
+            let source = "<script lang=\"ts\">a</script>\n\
+                          <script setup lang='tsx'>b</script>\n\
+                          <script lang=jsx>c</script>\n\
+                          <script lang=\"TypeScript\">d</script>\n\
+                          <script>e</script>";
+            assert_eq!(blocks_of(source, "vue"), vec![
+                ("a", "ts", true),
+                ("b", "tsx", true),
+                ("c", "jsx", true),
+                ("d", "ts", true),
+                ("e", "js", true),
+            ]);
+        }
+
+        #[test]
+        fn scripts_loaded_with_src_are_skipped() {
+            let source = "<script src=\"./chart.js\"></script>\n<script>let a;</script>";
+            assert_eq!(blocks_of(source, "svelte"), vec![("let a;", "js", true)]);
+        }
+
+        #[test]
+        fn scripts_in_comments_are_skipped() {
+            let source = "<!-- <script>old();</script> -->\n<script>let a;</script>";
+            assert_eq!(blocks_of(source, "svelte"), vec![("let a;", "js", true)]);
+            assert!(blocks_of("<!-- <script>old();</script>", "svelte").is_empty());
+        }
+
+        #[test]
+        fn greater_than_in_quoted_attributes_does_not_end_the_tag() {
+            let source = "<script data-rule=\"a > b\" lang='ts' title='>'>let a;</script>";
+            assert_eq!(blocks_of(source, "vue"), vec![("let a;", "ts", true)]);
+        }
+
+        #[test]
+        fn self_closing_scripts_have_no_content() {
+            let source = "<script/>\n<div>text</div>\n<script lang=\"ts\" />\n<script>let a;</script>";
+            assert_eq!(blocks_of(source, "vue"), vec![("let a;", "js", true)]);
+        }
+
+        #[test]
+        fn tag_names_are_case_insensitive_and_exact() {
+            let source = "<scripts>no</scripts>\n<SCRIPT>let a;</SCRIPT>";
+            assert_eq!(blocks_of(source, "svelte"), vec![("let a;", "js", true)]);
+        }
+
+        #[test]
+        fn unclosed_script_runs_to_the_end_of_the_file() {
+            assert_eq!(blocks_of("<script>let a;", "vue"), vec![("let a;", "js", true)]);
+        }
+
+        #[test]
+        fn page_scripts_are_modules_or_classic_by_type() {
+            let source = "<script type=\"module\">a</script>\n\
+                          <script>b</script>\n\
+                          <script type=\"text/javascript\">c</script>\n\
+                          <script type='application/ecmascript'>d</script>\n\
+                          <script type=\" Module \">e</script>";
+            assert_eq!(blocks_of(source, "html"), vec![
+                ("a", "js", true),
+                ("b", "js", false),
+                ("c", "js", false),
+                ("d", "js", false),
+                ("e", "js", true),
+            ]);
+            let ranges: Vec<_> = extract_script_blocks(source, "html").into_iter().map(|block| block.range).collect();
+            assert_eq!(ranges, vec![22..23, 41..42, 83..84, 132..133, 167..168]);
+        }
+
+        #[test]
+        fn page_data_blocks_are_skipped() {
+            let source = "<script type=\"application/json\">{\"a\": 1}</script>\n\
+                          <script type=\"importmap\">{}</script>\n\
+                          <script type=\"text/template\"><p>{{a}}</p></script>\n\
+                          <script>let a;</script>";
+            assert_eq!(blocks_of(source, "htm"), vec![("let a;", "js", false)]);
+        }
+
+        #[test]
+        fn page_scripts_ignore_lang() {
+            assert_eq!(blocks_of("<script lang=\"ts\">let a;</script>", "html"), vec![("let a;", "js", false)]);
+        }
+
+        #[test]
+        fn page_scripts_loaded_with_src_are_skipped() {
+            let source = "<script type=\"module\" src=\"./app.js\"></script>\n<script src=\"vendor.js\"></script>";
+            assert!(blocks_of(source, "html").is_empty());
+        }
+    }
+}
@@ -0,0 +1,142 @@
+pub mod watch {
+    use std::collections::BTreeMap;
+    use std::fs;
+    use std::io::{self, Write};
+    use std::path::{Path, PathBuf};
+    use std::sync::mpsc;
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+    use colored::Colorize;
+    use notify::{EventKind, RecursiveMode, Watcher};
+    use crate::Metrics;
+    use crate::common::history::history::format_timestamp;
+    use crate::parsing::{report_project, select_findings, AnalysisOptions, Project};
+    use crate::visitor::ModuleAnalysis;
+
+    // Editors write a file in several steps, events arriving this close together are analyzed at once
+    const DEBOUNCE: Duration = Duration::from_millis(200);
+
+    /// A finding as tracked between analyses: file, qualified name and the occurrence of that name in the file.
+    type FindingKey = (String, String, usize);
+
+    struct WatchedFinding {
+        location: String,
+        score: usize,
+    }
+
+    /// Reports the analysis once, then re-analyzes the files changing below `path`
+    /// and writes only how the findings changed. Returns when the watcher fails.
+    pub fn watch_input(path: &Path, thresholds: &Metrics, options: &AnalysisOptions, out: &mut dyn Write) -> Result<(), String> {
+        let mut project = Project::load(path, options);
+        report_project(&project, thresholds, options, out);
+        let mut findings = _findings(&project, thresholds, options);
+
+        let watched = fs::canonicalize(&project.root).map_err(|e| format!("{}: {}", project.root.display(), e))?;
+        let (sender, receiver) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(sender).map_err(|e| e.to_string())?;
+        watcher.watch(&watched, RecursiveMode::Recursive).map_err(|e| e.to_string())?;
+        _write_line(out, &format!("Watching {} for changes", project.root.display()));
+
+        loop {
+            let mut changed_paths: Vec<PathBuf> = Vec::new();
+            let mut next = receiver.recv().map_err(|e| e.to_string())?;
+            loop {
+                let event = next.map_err(|e| e.to_string())?;
+                let paths = event.paths.iter()
+                    // Only new directories bring files, other events on them come with events on their files
+                    .filter(|changed| !changed.is_dir() || matches!(event.kind, EventKind::Create(_)))
+                    .map(|changed| _project_path(&project.root, &watched, changed));
+                if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)) {
+                    changed_paths.extend(paths);
+                }
+                match receiver.recv_timeout(DEBOUNCE) {
+                    Ok(event) => next = event,
+                    Err(mpsc::RecvTimeoutError::Timeout) => break,
+                    Err(e) => return Err(e.to_string()),
+                }
+            }
+            changed_paths.sort();
+            changed_paths.dedup();
+
+            if project.update(&changed_paths, options) == 0 {
+                continue;
+            }
+            for e in project.parse_errors_below(&changed_paths) {
+                eprintln!("Error parsing {}", e);
+            }
+
+            let updated = _findings(&project, thresholds, options);
+            let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|elapsed| elapsed.as_secs()).unwrap_or_default();
+            _write_line(out, &format!("\n[{}] {} findings", format_timestamp(now), updated.len()));
+            if let Err(e) = _write_diff(&findings, &updated, out) {
+                eprintln!("Error writing report: {}", e);
+            }
+            findings = updated;
+        }
+    }
+
+    fn _findings(project: &Project, thresholds: &Metrics, options: &AnalysisOptions) -> BTreeMap<FindingKey, WatchedFinding> {
+        let modules = project.modules();
+        let selection = select_findings(&modules, &project.source_map, thresholds, options);
+
+        let mut findings = BTreeMap::new();
+        for &(id, score) in &selection.findings {
+            let function = ModuleAnalysis::function(&modules, id);
+            let location = project.source_map.lookup_char_pos(function.span.lo);
+            let file = location.file.name.to_string();
+            let occurrence = findings.keys()
+                .filter(|(other_file, other_name, _): &&FindingKey| *other_file == file && *other_name == function.qualified_name)
+                .count();
+            let key = (file, function.qualified_name.clone(), occurrence);
+            let location = format!("{}:{}:{}", location.file.name, location.line, location.col_display);
+            findings.insert(key, WatchedFinding { location, score });
+        }
+        findings
+    }
+
+    fn _write_diff(
+        before: &BTreeMap<FindingKey, WatchedFinding>,
+        after: &BTreeMap<FindingKey, WatchedFinding>,
+        out: &mut dyn Write,
+    ) -> io::Result<()> {
+        let mut unchanged = true;
+        for (key, finding) in after {
+            match before.get(key) {
+                None => writeln!(out, "{} {} {} {}/10", "New".red(), key.1, finding.location, finding.score)?,
+                Some(previous) if previous.score != finding.score => writeln!(
+                    out,
+                    "{} {} {} {}/10 -> {}/10",
+                    "Score changed".yellow(),
+                    key.1,
+                    finding.location,
+                    previous.score,
+                    finding.score
+                )?,
+                Some(_) => continue,
+            }
+            unchanged = false;
+        }
+        for (key, finding) in before.iter().filter(|(key, _)| !after.contains_key(key)) {
+            writeln!(out, "{} {} {}", "Resolved".green(), key.1, finding.location)?;
+            unchanged = false;
+        }
+        if unchanged {
+            writeln!(out, "No changes in findings")?;
+        }
+        out.flush()
+    }
+
+    /// The changed path as the project refers to it, below its possibly relative root.
+    fn _project_path(root: &Path, watched: &Path, changed: &Path) -> PathBuf {
+        match changed.strip_prefix(watched) {
+            Ok(relative) if relative.as_os_str().is_empty() => root.to_path_buf(),
+            Ok(relative) => root.join(relative),
+            Err(_) => changed.to_path_buf(),
+        }
+    }
+
+    fn _write_line(out: &mut dyn Write, line: &str) {
+        if let Err(e) = writeln!(out, "{}", line).and_then(|_| out.flush()) {
+            eprintln!("Error writing report: {}", e);
+        }
+    }
+}
@@ -0,0 +1,437 @@
+pub mod call_graph {
+    use std::collections::HashSet;
+    use swc_ecma_ast::{
+        ArrowExpr, CallExpr, Callee, DoWhileStmt, Expr, ForInStmt, ForOfStmt, ForStmt, Function, MemberProp,
+        WhileStmt,
+    };
+    use swc_ecma_visit::{Visit, VisitWith};
+    use crate::visitor::FunctionAnalysis;
+    use crate::visitor::function_analysis::function_analysis::{CallSite, FunctionId, ModuleAnalysis, Recursion};
+    use crate::visitor::module_links::module_links::{Export, ImportedName, DEFAULT_EXPORT};
+
+    // Guards against re-export cycles between modules
+    const MAX_REEXPORT_DEPTH: usize = 8;
+
+    /// Calls of a single function body made through `name()` or `object.name()`.
+    pub fn collect_callees<B: VisitWith<CalleeVisitor>>(body: &B) -> Vec<CallSite> {
+        let mut visitor = CalleeVisitor::default();
+        body.visit_with(&mut visitor);
+        visitor.callees
+    }
+
+    #[derive(Default)]
+    pub struct CalleeVisitor {
+        callees: Vec<CallSite>,
+        loop_depth: usize,
+    }
+
+    impl CalleeVisitor {
+        fn visit_loop_body<N: VisitWith<Self>>(&mut self, body: &N) {
+            self.loop_depth += 1;
+            body.visit_with(self);
+            self.loop_depth -= 1;
+        }
+    }
+
+    impl Visit for CalleeVisitor {
+        // Calls made by nested functions belong to those functions
+        fn visit_function(&mut self, _: &Function) {}
+        fn visit_arrow_expr(&mut self, _: &ArrowExpr) {}
+
+        fn visit_call_expr(&mut self, n: &CallExpr) {
+            n.visit_children_with(self);
+            let Callee::Expr(callee) = &n.callee else {
+                return;
+            };
+            let name = match &**callee {
+                Expr::Ident(ident) => ident.sym.to_string(),
+                Expr::Member(member) => match (&*member.obj, &member.prop) {
+                    (Expr::Ident(obj), MemberProp::Ident(prop)) => format!("{}.{}", obj.sym, prop.sym),
+                    _ => return,
+                },
+                _ => return,
+            };
+
+            // Only the deepest call site of each callee matters
+            match self.callees.iter_mut().find(|site| site.callee == name) {
+                Some(site) => site.loop_depth = site.loop_depth.max(self.loop_depth),
+                None => self.callees.push(CallSite { callee: name, loop_depth: self.loop_depth }),
+            }
+        }
+
+        fn visit_for_stmt(&mut self, n: &ForStmt) {
+            n.init.visit_with(self);
+            n.test.visit_with(self);
+            n.update.visit_with(self);
+            self.visit_loop_body(&n.body);
+        }
+
+        fn visit_for_in_stmt(&mut self, n: &ForInStmt) {
+            n.right.visit_with(self);
+            self.visit_loop_body(&n.body);
+        }
+
+        fn visit_for_of_stmt(&mut self, n: &ForOfStmt) {
+            n.right.visit_with(self);
+            self.visit_loop_body(&n.body);
+        }
+
+        fn visit_while_stmt(&mut self, n: &WhileStmt) {
+            n.test.visit_with(self);
+            self.visit_loop_body(&n.body);
+        }
+
+        fn visit_do_while_stmt(&mut self, n: &DoWhileStmt) {
+            self.visit_loop_body(&n.body);
+            n.test.visit_with(self);
+        }
+    }
+
+    /// Links the functions of all analyzed modules through their calls, imports and exports,
+    /// then fills in recursion, call depth and the loop nesting callers invoke each function from.
+    pub fn analyze_calls(modules: &mut [ModuleAnalysis]) {
+        let mut offsets = Vec::with_capacity(modules.len());
+        let mut node_count = 0;
+        for module in modules.iter() {
+            offsets.push(node_count);
+            node_count += module.functions.len();
+        }
+
+        // Edges carry the loop depth of the call site inside the caller
+        let mut edges: Vec<Vec<(usize, usize)>> = vec![Vec::new(); node_count];
+        for (module_index, module) in modules.iter().enumerate() {
+            for (function_index, function) in module.functions.iter().enumerate() {
+                let caller = offsets[module_index] + function_index;
+                for site in &function.callees {
+                    for (callee_module, callee_function) in _resolve_callee(modules, module_index, &site.callee) {
+                        edges[caller].push((offsets[callee_module] + callee_function, site.loop_depth));
+                    }
+                }
+            }
+        }
+
+        let successors: Vec<HashSet<usize>> = edges.iter()
+            .map(|node_edges| node_edges.iter().map(|&(callee, _)| callee).collect())
+            .collect();
+        let components = _strongly_connected_components(&successors);
+        let mut component_of = vec![0; node_count];
+        for (component_index, component) in components.iter().enumerate() {
+            for &node in component {
+                component_of[node] = component_index;
+            }
+        }
+
+        // Tarjan yields components callees first, so call depths fill in a single forward pass
+        let mut component_depth = vec![0; components.len()];
+        for (component_index, component) in components.iter().enumerate() {
+            component_depth[component_index] = component.iter()
+                .flat_map(|&node| successors[node].iter())
+                .map(|&callee| component_of[callee])
+                .filter(|&callee_component| callee_component != component_index)
+                .map(|callee_component| component_depth[callee_component] + 1)
+                .max()
+                .unwrap_or(0);
+        }
+
+        // ... and callers first in reverse, which lets loop nesting flow down to the callees
+        let mut component_loop_depth = vec![0; components.len()];
+        for (component_index, component) in components.iter().enumerate().rev() {
+            for &node in component {
+                for &(callee, site_depth) in &edges[node] {
+                    let callee_component = component_of[callee];
+                    if callee_component != component_index {
+                        let propagated = component_loop_depth[component_index] + site_depth;
+                        component_loop_depth[callee_component] = component_loop_depth[callee_component].max(propagated);
+                    }
+                }
+            }
+        }
+
+        let mut recursion = vec![Recursion::None; node_count];
+        for component in &components {
+            if component.len() > 1 {
+                let mut names: Vec<String> = component.iter()
+                    .map(|&node| _function_at(modules, &offsets, node).display_name().to_string())
+                    .collect();
+                names.sort();
+                for &node in component {
+                    recursion[node] = Recursion::Mutual(names.clone());
+                }
+            } else if successors[component[0]].contains(&component[0]) {
+                recursion[component[0]] = Recursion::Direct;
+            }
+        }
+
+        for (module_index, module) in modules.iter_mut().enumerate() {
+            for (function_index, function) in module.functions.iter_mut().enumerate() {
+                let node = offsets[module_index] + function_index;
+                function.recursion = std::mem::take(&mut recursion[node]);
+                function.call_depth = component_depth[component_of[node]];
+                function.caller_loop_depth = component_loop_depth[component_of[node]];
+                function.calls = successors[node].iter()
+                    .map(|&callee| _function_id(&offsets, callee))
+                    .collect();
+                function.calls.sort();
+            }
+        }
+    }
+
+    fn _function_at<'m>(modules: &'m [ModuleAnalysis], offsets: &[usize], node: usize) -> &'m FunctionAnalysis {
+        ModuleAnalysis::function(modules, _function_id(offsets, node))
+    }
+
+    fn _function_id(offsets: &[usize], node: usize) -> FunctionId {
+        // Modules without functions share their offset with the next one
+        let module = offsets.partition_point(|&offset| offset <= node) - 1;
+        FunctionId { module, function: node - offsets[module] }
+    }
+
+    /// Functions a call site may invoke, as (module, function) indices.
+    fn _resolve_callee(modules: &[ModuleAnalysis], module_index: usize, callee: &str) -> Vec<(usize, usize)> {
+        match callee.split_once('.') {
+            Some((object, property)) => modules[module_index].links.imports.iter()
+                .filter(|import| import.local == object && import.imported == ImportedName::Namespace)
+                .filter_map(|import| import.source.resolved)
+                .flat_map(|target| _resolve_export(modules, target, property, 0))
+                .collect(),
+            None => _resolve_local(modules, module_index, callee, 0),
+        }
+    }
+
+    /// A name in module scope: a function defined there, or one it imports.
+    fn _resolve_local(modules: &[ModuleAnalysis], module_index: usize, name: &str, depth: usize) -> Vec<(usize, usize)> {
+        let local: Vec<(usize, usize)> = modules[module_index].functions.iter().enumerate()
+            .filter(|(_, function)| function.name.as_deref() == Some(name))
+            .map(|(function_index, _)| (module_index, function_index))
+            .collect();
+        if !local.is_empty() || depth > MAX_REEXPORT_DEPTH {
+            return local;
+        }
+
+        modules[module_index].links.imports.iter()
+            .filter(|import| import.local == name)
+            .filter_map(|import| {
+                let exported = match &import.imported {
+                    ImportedName::Named(imported) => imported.as_str(),
+                    // A whole CommonJS module called directly is its `module.exports = fn`
+                    ImportedName::Default | ImportedName::Namespace => DEFAULT_EXPORT,
+                };
+                import.source.resolved.map(|target| (target, exported))
+            })
+            .flat_map(|(target, exported)| _resolve_export(modules, target, exported, depth + 1))
+            .collect()
+    }
+
+    fn _resolve_export(modules: &[ModuleAnalysis], module_index: usize, exported_name: &str, depth: usize) -> Vec<(usize, usize)> {
+        if depth > MAX_REEXPORT_DEPTH {
+            return Vec::new();
+        }
+
+        let mut resolved = Vec::new();
+        for export in &modules[module_index].links.exports {
+            match export {
+                Export::Local { exported, local } if exported == exported_name => {
+                    resolved.extend(_resolve_local(modules, module_index, local, depth + 1));
+                }
+                Export::Reexport { exported, imported, source } if exported == exported_name => {
+                    if let Some(target) = source.resolved {
+                        resolved.extend(_resolve_export(modules, target, imported, depth + 1));
+                    }
+                }
+                // `export *` never forwards the default export
+                Export::ReexportAll { source } if exported_name != DEFAULT_EXPORT => {
+                    if let Some(target) = source.resolved {
+                        resolved.extend(_resolve_export(modules, target, exported_name, depth + 1));
+                    }
+                }
+                _ => {}
+            }
+        }
+        resolved
+    }
+
+    /// Tarjan's algorithm, components are returned in reverse topological order.
+    fn _strongly_connected_components(graph: &[HashSet<usize>]) -> Vec<Vec<usize>> {
+        struct Tarjan<'g> {
+            graph: &'g [HashSet<usize>],
+            index: Vec<Option<usize>>,
+            low_link: Vec<usize>,
+            on_stack: Vec<bool>,
+            stack: Vec<usize>,
+            next_index: usize,
+            components: Vec<Vec<usize>>,
+        }
+
+        impl<'g> Tarjan<'g> {
+            fn connect(&mut self, node: usize) {
+                self.index[node] = Some(self.next_index);
+                self.low_link[node] = self.next_index;
+                self.next_index += 1;
+                self.stack.push(node);
+                self.on_stack[node] = true;
+
+                for &successor in self.graph[node].iter() {
+                    match self.index[successor] {
+                        None => {
+                            self.connect(successor);
+                            self.low_link[node] = self.low_link[node].min(self.low_link[successor]);
+                        }
+                        Some(successor_index) if self.on_stack[successor] => {
+                            self.low_link[node] = self.low_link[node].min(successor_index);
+                        }
+                        _ => {}
+                    }
+                }
+
+                if Some(self.low_link[node]) == self.index[node] {
+                    let mut component = Vec::new();
+                    while let Some(member) = self.stack.pop() {
+                        self.on_stack[member] = false;
+                        component.push(member);
+                        if member == node {
+                            break;
+                        }
+                    }
+                    self.components.push(component);
+                }
+            }
+        }
+
+        let mut tarjan = Tarjan {
+            graph,
+            index: vec![None; graph.len()],
+            low_link: vec![0; graph.len()],
+            on_stack: vec![false; graph.len()],
+            stack: Vec::new(),
+            next_index: 0,
+            components: Vec::new(),
+        };
+        for node in 0..graph.len() {
+            if tarjan.index[node].is_none() {
+                tarjan.connect(node);
+            }
+        }
+        tarjan.components
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::path::{Path, PathBuf};
+        use crate::fixtures::fixtures::{analyze_module, parse};
+        use crate::visitor::{collect_module_links, FunctionAnalysis, Suppressions};
+        use crate::visitor::function_analysis::function_analysis::{ModuleAnalysis, Recursion};
+        use super::analyze_calls;
+
+        /// Analyzes named modules, `./name` specifiers resolve to the module of that name.
+        fn analyze(sources: &[(&str, &str)]) -> Vec<ModuleAnalysis> {
+            let names: Vec<&str> = sources.iter().map(|(name, _)| *name).collect();
+            let mut modules: Vec<ModuleAnalysis> = sources.iter()
+                .map(|(name, source)| {
+                    let module = parse(source);
+                    let mut links = collect_module_links(&module);
+                    for link in links.sources_mut() {
+                        link.resolved = names.iter().position(|name| link.specifier.strip_prefix("./") == Some(*name));
+                    }
+                    ModuleAnalysis { path: PathBuf::from(name), functions: analyze_module(&module), links, suppressions: Suppressions::default() }
+                })
+                .collect();
+            analyze_calls(&mut modules);
+            modules
+        }
+
+        fn function<'m>(modules: &'m [ModuleAnalysis], module: &str, name: &str) -> &'m FunctionAnalysis {
+            modules.iter()
+                .find(|analysis| analysis.path == Path::new(module))
+                .and_then(|analysis| analysis.functions.iter().find(|function| function.name.as_deref() == Some(name)))
+                .unwrap_or_else(|| panic!("No function {} in {}", name, module))
+        }
+
+        /// Calls of a function as `module:function`.
+        fn calls(modules: &[ModuleAnalysis], module: &str, name: &str) -> Vec<String> {
+            function(modules, module, name).calls.iter()
+                .map(|&id| format!("{}:{}", modules[id.module].path.display(), ModuleAnalysis::function(modules, id).display_name()))
+                .collect()
+        }
+
+        #[test]
+        fn named_import_links_the_call_and_its_loop_depth() {
+            let modules = analyze(&[
+                ("main", "import { mix } from './math';\nfunction main(a) { for (const x of a) { mix(x); } }"),
+                ("math", "export function mix(x) { return x * 2; }"),
+            ]);
+            assert_eq!(calls(&modules, "main", "main"), vec!["math:mix"]);
+            assert_eq!(function(&modules, "math", "mix").caller_loop_depth, 1);
+            assert_eq!(function(&modules, "main", "main").call_depth, 1);
+        }
+
+        #[test]
+        fn namespace_import_links_member_calls() {
+            let modules = analyze(&[
+                ("main", "import * as math from './math';\nfunction main() { math.mix(); math.missing(); }"),
+                ("math", "function lerp() {}\nexport { lerp as mix };"),
+            ]);
+            assert_eq!(calls(&modules, "main", "main"), vec!["math:lerp"]);
+        }
+
+        #[test]
+        fn calls_follow_reexport_chains() {
+            let modules = analyze(&[
+                ("main", "import { blend, area } from './index';\nfunction main() { blend(); area(); }"),
+                ("index", "export { mix as blend } from './colors';\nexport * from './geo';"),
+                ("colors", "export { lerp as mix } from './math';"),
+                ("math", "export function lerp() {}"),
+                ("geo", "export function area() {}"),
+            ]);
+            assert_eq!(calls(&modules, "main", "main"), vec!["math:lerp", "geo:area"]);
+        }
+
+        #[test]
+        fn reexport_all_does_not_forward_the_default_export() {
+            let modules = analyze(&[
+                ("main", "import area from './index';\nfunction main() { area(); }"),
+                ("index", "export * from './geo';"),
+                ("geo", "export default function area() {}"),
+            ]);
+            assert!(calls(&modules, "main", "main").is_empty());
+        }
+
+        #[test]
+        fn reexport_cycles_end() {
+            let modules = analyze(&[
+                ("main", "import { area } from './a';\nfunction main() { area(); }"),
+                ("a", "export * from './b';"),
+                ("b", "export * from './a';"),
+            ]);
+            assert!(calls(&modules, "main", "main").is_empty());
+        }
+
+        #[test]
+        fn require_links_namespace_and_destructured_calls() {
+            let modules = analyze(&[
+                ("main", "const lib = require('./lib');\nconst { scale } = require('./lib');\nfunction main() { lib.mix(); lib.blur(); scale(); }"),
+                ("lib", "function mix() {}\nmodule.exports = { mix, scale: function () {} };\nexports.blur = () => {};"),
+            ]);
+            assert_eq!(calls(&modules, "main", "main"), vec!["lib:mix", "lib:scale", "lib:blur"]);
+        }
+
+        #[test]
+        fn required_module_called_directly_is_its_module_exports() {
+            let modules = analyze(&[
+                ("main", "const run = require('./run');\nfunction start() { while (true) { run(); } }"),
+                ("run", "function run() {}\nmodule.exports = run;"),
+            ]);
+            assert_eq!(calls(&modules, "main", "start"), vec!["run:run"]);
+            assert_eq!(function(&modules, "run", "run").caller_loop_depth, 1);
+        }
+
+        #[test]
+        fn mutual_recursion_across_modules_is_detected() {
+            let modules = analyze(&[
+                ("even", "import { isOdd } from './odd';\nexport function isEven(n) { return n === 0 || isOdd(n - 1); }"),
+                ("odd", "import { isEven } from './even';\nexport function isOdd(n) { return n !== 0 && isEven(n - 1); }"),
+            ]);
+            let recursion = &function(&modules, "even", "isEven").recursion;
+            assert_eq!(*recursion, Recursion::Mutual(vec!["isEven".to_string(), "isOdd".to_string()]));
+        }
+    }
+}
@@ -0,0 +1,207 @@
+pub mod clustering {
+    use std::collections::{BTreeSet, HashMap};
+    use crate::visitor::function_analysis::function_analysis::{FunctionId, ModuleAnalysis};
+
+    /// Candidates calling each other, suggested to be ported together as one WASM module
+    /// so that calls between them do not cross the JS/WASM boundary.
+    pub struct Cluster {
+        pub entry_point: FunctionId,
+        /// Flagged functions of the module, the entry point included
+        pub candidates: Vec<FunctionId>,
+        /// Functions of the module that remaining JS code calls
+        pub boundary: Vec<FunctionId>,
+        /// Functions below the score threshold the candidates depend on
+        pub helpers: Vec<FunctionId>,
+        pub combined_score: usize,
+    }
+
+    /// Groups candidates (functions with a score) that reach each other through calls,
+    /// directly or through helpers. Candidates without any such partner are left out.
+    pub fn find_clusters(modules: &[ModuleAnalysis], scores: &HashMap<FunctionId, usize>) -> Vec<Cluster> {
+        let mut candidates: Vec<FunctionId> = scores.keys().copied().collect();
+        candidates.sort();
+        let reachable: HashMap<FunctionId, BTreeSet<FunctionId>> = candidates.iter()
+            .map(|&candidate| (candidate, _reachable_from(modules, candidate)))
+            .collect();
+
+        let mut groups: Vec<BTreeSet<FunctionId>> = Vec::new();
+        for &candidate in &candidates {
+            let connected: BTreeSet<FunctionId> = reachable[&candidate].iter()
+                .copied()
+                .filter(|function| scores.contains_key(function))
+                .chain([candidate])
+                .collect();
+            // Merge every existing group sharing a candidate with this one
+            let (overlapping, mut rest): (Vec<_>, Vec<_>) = groups.into_iter()
+                .partition(|group| !group.is_disjoint(&connected));
+            rest.push(overlapping.into_iter().flatten().chain(connected).collect());
+            groups = rest;
+        }
+
+        let mut clusters: Vec<Cluster> = groups.into_iter()
+            .filter(|group| group.len() > 1)
+            .map(|group| _build_cluster(modules, scores, &reachable, group))
+            .collect();
+        clusters.sort_by(|a, b| b.combined_score.cmp(&a.combined_score).then(a.entry_point.cmp(&b.entry_point)));
+        clusters
+    }
+
+    fn _build_cluster(
+        modules: &[ModuleAnalysis],
+        scores: &HashMap<FunctionId, usize>,
+        reachable: &HashMap<FunctionId, BTreeSet<FunctionId>>,
+        group: BTreeSet<FunctionId>,
+    ) -> Cluster {
+        let helpers: BTreeSet<FunctionId> = group.iter()
+            .flat_map(|candidate| reachable[candidate].iter().copied())
+            .filter(|function| !group.contains(function))
+            .collect();
+        let inside: BTreeSet<FunctionId> = group.union(&helpers).copied().collect();
+
+        let mut called_from_inside = BTreeSet::new();
+        let mut called_from_outside = BTreeSet::new();
+        for (module_index, module) in modules.iter().enumerate() {
+            for (function_index, function) in module.functions.iter().enumerate() {
+                let caller = FunctionId { module: module_index, function: function_index };
+                for &callee in function.calls.iter().filter(|&&callee| callee != caller && inside.contains(&callee)) {
+                    if inside.contains(&caller) {
+                        called_from_inside.insert(callee);
+                    } else {
+                        called_from_outside.insert(callee);
+                    }
+                }
+            }
+        }
+
+        // Prefer a candidate nothing in the module calls, then the one reaching most of the others
+        let entry_point = *group.iter()
+            .max_by_key(|&candidate| (
+                !called_from_inside.contains(candidate),
+                reachable[candidate].intersection(&group).count(),
+                scores[candidate],
+                std::cmp::Reverse(*candidate),
+            ))
+            .expect("clusters are never empty");
+
+        called_from_outside.insert(entry_point);
+        Cluster {
+            entry_point,
+            candidates: group.iter().copied().collect(),
+            boundary: called_from_outside.into_iter().collect(),
+            helpers: helpers.into_iter().collect(),
+            combined_score: group.iter().map(|candidate| scores[candidate]).sum(),
+        }
+    }
+
+    fn _reachable_from(modules: &[ModuleAnalysis], start: FunctionId) -> BTreeSet<FunctionId> {
+        let mut reached = BTreeSet::new();
+        let mut pending = vec![start];
+        while let Some(function) = pending.pop() {
+            for &callee in &ModuleAnalysis::function(modules, function).calls {
+                if callee != start && reached.insert(callee) {
+                    pending.push(callee);
+                }
+            }
+        }
+        reached
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::collections::HashMap;
+        use std::path::Path;
+        use crate::parsing::Project;
+        use crate::visitor::function_analysis::function_analysis::{FunctionId, ModuleAnalysis};
+        use super::find_clusters;
+
+        /// Entry point, candidates, boundary, helpers and combined score of each cluster, by function name.
+        type NamedCluster = (String, Vec<String>, Vec<String>, Vec<String>, usize);
+
+        fn clusters(source: &str, scores: &[(&str, usize)]) -> Vec<NamedCluster> {
+            let project = Project::from_source(Path::new("cluster.js"), source.to_string());
+            let modules = project.modules();
+            let id_of = |name: &str| {
+                let function = modules[0].functions.iter()
+                    .position(|function| function.display_name() == name)
+                    .unwrap_or_else(|| panic!("No function {}", name));
+                FunctionId { module: 0, function }
+            };
+            let scores: HashMap<FunctionId, usize> = scores.iter().map(|&(name, score)| (id_of(name), score)).collect();
+
+            let name_of = |id: &FunctionId| ModuleAnalysis::function(&modules, *id).display_name().to_string();
+            let names = |ids: &[FunctionId]| ids.iter().map(name_of).collect::<Vec<_>>();
+            find_clusters(&modules, &scores).iter()
+                .map(|cluster| {
+                    (name_of(&cluster.entry_point), names(&cluster.candidates), names(&cluster.boundary), names(&cluster.helpers), cluster.combined_score)
+                })
+                .collect()
+        }
+
+        fn names(names: &[&str]) -> Vec<String> {
+            names.iter().map(|name| name.to_string()).collect()
+        }
+
+        #[test]
+        fn mutually_calling_candidates_are_grouped() {
+            let source = "function a(n) { return b(n); }\nfunction b(n) { return a(n); }\nfunction c() {}";
+            let found = clusters(source, &[("a", 5), ("b", 4), ("c", 7)]);
+            // `c` has no partner and is not suggested
+            assert_eq!(found, vec![("a".to_string(), names(&["a", "b"]), names(&["a"]), vec![], 9)]);
+        }
+
+        #[test]
+        fn call_chains_join_one_cluster() {
+            let source = "function run() { step(); }\nfunction step() { leaf(); }\nfunction leaf() {}\nfunction other() {}";
+            let found = clusters(source, &[("run", 3), ("step", 2), ("leaf", 8), ("other", 9)]);
+            assert_eq!(found.len(), 1);
+            assert_eq!(found[0].1, names(&["run", "step", "leaf"]));
+            assert_eq!(found[0].4, 13);
+        }
+
+        #[test]
+        fn entry_point_is_the_candidate_nothing_inside_calls() {
+            // `leaf` scores highest, but `run` is where the cluster is entered
+            let source = "function run() { step(); }\nfunction step() { leaf(); }\nfunction leaf() {}";
+            let found = clusters(source, &[("run", 1), ("step", 2), ("leaf", 10)]);
+            assert_eq!(found[0].0, "run");
+        }
+
+        #[test]
+        fn entry_point_of_a_cycle_reaches_most_then_scores_highest() {
+            let cycle = "function a() { b(); }\nfunction b() { c(); a(); }\nfunction c() { b(); }";
+            // Everyone is called from inside and reaches both others
+            assert_eq!(clusters(cycle, &[("a", 2), ("b", 3), ("c", 6)])[0].0, "c");
+            // Equal scores fall back to source order
+            assert_eq!(clusters(cycle, &[("a", 4), ("b", 4), ("c", 4)])[0].0, "a");
+        }
+
+        #[test]
+        fn functions_called_from_outside_are_on_the_boundary() {
+            let source = "function run() { step(); }\nfunction step() { run(); }\nfunction ui() { step(); }";
+            let found = clusters(source, &[("run", 5), ("step", 5)]);
+            assert_eq!(found[0].2, names(&["run", "step"]));
+        }
+
+        #[test]
+        fn candidates_sharing_only_a_caller_are_not_merged() {
+            let source = "function main() { a(); b(); }\nfunction a() {}\nfunction b() {}";
+            assert_eq!(clusters(source, &[("a", 5), ("b", 5)]), vec![]);
+
+            // Two separate pairs behind the same non-candidate, strongest first
+            let pairs = "function main() { a(); c(); }\n\
+                         function a() { b(); }\nfunction b() {}\n\
+                         function c() { d(); }\nfunction d() {}";
+            let found = clusters(pairs, &[("a", 1), ("b", 1), ("c", 5), ("d", 5)]);
+            let candidates: Vec<&Vec<String>> = found.iter().map(|cluster| &cluster.1).collect();
+            assert_eq!(candidates, vec![&names(&["c", "d"]), &names(&["a", "b"])]);
+        }
+
+        #[test]
+        fn helpers_join_candidates_and_are_listed_apart() {
+            // `bridge` is not a candidate, yet `a` reaches `b` through it
+            let source = "function a() { bridge(); util(); }\nfunction bridge() { b(); }\nfunction b() {}\nfunction util() {}\nfunction unused() {}";
+            let found = clusters(source, &[("a", 6), ("b", 3)]);
+            assert_eq!(found, vec![("a".to_string(), names(&["a", "b"]), names(&["a"]), names(&["bridge", "util"]), 9)]);
+        }
+    }
+}
@@ -0,0 +1,277 @@
+pub mod cognitive_complexity {
+    use swc_ecma_ast::{
+        ArrowExpr, BinExpr, BinaryOp, BreakStmt, CallExpr, Callee, CondExpr, ContinueStmt, DoWhileStmt, Expr,
+        ForInStmt, ForOfStmt, ForStmt, Function, IfStmt, Stmt, SwitchStmt, TryStmt, WhileStmt,
+    };
+    use swc_ecma_visit::{Visit, VisitWith};
+
+    /// Measures Sonar-style cognitive complexity of a single function body:
+    /// every break in the linear flow costs 1, plus 1 for each level of nesting it sits in.
+    pub fn measure<B>(function_name: Option<&str>, body: &B) -> usize
+    where
+        B: for<'n> VisitWith<CognitiveComplexityVisitor<'n>>,
+    {
+        let mut visitor = CognitiveComplexityVisitor {
+            function_name,
+            nesting: 0,
+            complexity: 0,
+        };
+        body.visit_with(&mut visitor);
+        visitor.complexity
+    }
+
+    pub struct CognitiveComplexityVisitor<'n> {
+        function_name: Option<&'n str>,
+        nesting: usize,
+        complexity: usize,
+    }
+
+    impl<'n> CognitiveComplexityVisitor<'n> {
+        fn increment_with_nesting(&mut self) {
+            self.complexity += 1 + self.nesting;
+        }
+
+        fn visit_nested<N: VisitWith<Self>>(&mut self, n: &N) {
+            self.nesting += 1;
+            n.visit_with(self);
+            self.nesting -= 1;
+        }
+
+        // `else if` and `else` add a flat increment, they do not nest deeper than the `if`
+        fn visit_else_branch(&mut self, alt: Option<&Stmt>) {
+            match alt {
+                Some(Stmt::If(else_if)) => {
+                    self.complexity += 1;
+                    else_if.test.visit_with(self);
+                    self.visit_nested(&else_if.cons);
+                    self.visit_else_branch(else_if.alt.as_deref());
+                }
+                Some(else_stmt) => {
+                    self.complexity += 1;
+                    self.visit_nested(else_stmt);
+                }
+                None => {}
+            }
+        }
+    }
+
+    impl<'n> Visit for CognitiveComplexityVisitor<'n> {
+        // Nested functions are measured on their own
+        fn visit_function(&mut self, _: &Function) {}
+        fn visit_arrow_expr(&mut self, _: &ArrowExpr) {}
+
+        fn visit_if_stmt(&mut self, n: &IfStmt) {
+            self.increment_with_nesting();
+            n.test.visit_with(self);
+            self.visit_nested(&n.cons);
+            self.visit_else_branch(n.alt.as_deref());
+        }
+
+        fn visit_switch_stmt(&mut self, n: &SwitchStmt) {
+            self.increment_with_nesting();
+            n.discriminant.visit_with(self);
+            self.visit_nested(&n.cases);
+        }
+
+        fn visit_for_stmt(&mut self, n: &ForStmt) {
+            self.increment_with_nesting();
+            n.init.visit_with(self);
+            n.test.visit_with(self);
+            n.update.visit_with(self);
+            self.visit_nested(&n.body);
+        }
+
+        fn visit_for_in_stmt(&mut self, n: &ForInStmt) {
+            self.increment_with_nesting();
+            n.right.visit_with(self);
+            self.visit_nested(&n.body);
+        }
+
+        fn visit_for_of_stmt(&mut self, n: &ForOfStmt) {
+            self.increment_with_nesting();
+            n.right.visit_with(self);
+            self.visit_nested(&n.body);
+        }
+
+        fn visit_while_stmt(&mut self, n: &WhileStmt) {
+            self.increment_with_nesting();
+            n.test.visit_with(self);
+            self.visit_nested(&n.body);
+        }
+
+        fn visit_do_while_stmt(&mut self, n: &DoWhileStmt) {
+            self.increment_with_nesting();
+            self.visit_nested(&n.body);
+            n.test.visit_with(self);
+        }
+
+        fn visit_try_stmt(&mut self, n: &TryStmt) {
+            n.block.visit_with(self);
+            if let Some(handler) = &n.handler {
+                self.increment_with_nesting();
+                self.visit_nested(&handler.body);
+            }
+            n.finalizer.visit_with(self);
+        }
+
+        fn visit_cond_expr(&mut self, n: &CondExpr) {
+            self.increment_with_nesting();
+            n.test.visit_with(self);
+            self.visit_nested(&n.cons);
+            self.visit_nested(&n.alt);
+        }
+
+        fn visit_break_stmt(&mut self, n: &BreakStmt) {
+            if n.label.is_some() {
+                self.complexity += 1;
+            }
+        }
+
+        fn visit_continue_stmt(&mut self, n: &ContinueStmt) {
+            if n.label.is_some() {
+                self.complexity += 1;
+            }
+        }
+
+        // Each run of identical logical operators (`a && b && c || d` has two) costs 1
+        fn visit_bin_expr(&mut self, n: &BinExpr) {
+            if !_is_logical(n.op) {
+                n.visit_children_with(self);
+                return;
+            }
+
+            let mut operators = Vec::new();
+            let mut operands = Vec::new();
+            _flatten_logical(n, &mut operators, &mut operands);
+
+            operators.dedup();
+            self.complexity += operators.len();
+            for operand in operands {
+                operand.visit_with(self);
+            }
+        }
+
+        fn visit_call_expr(&mut self, n: &CallExpr) {
+            n.visit_children_with(self);
+            if let (Some(function_name), Callee::Expr(callee)) = (self.function_name, &n.callee) {
+                if matches!(&**callee, Expr::Ident(ident) if ident.sym.as_ref() == function_name) {
+                    self.complexity += 1; // Recursion
+                }
+            }
+        }
+    }
+
+    fn _is_logical(op: BinaryOp) -> bool {
+        matches!(op, BinaryOp::LogicalAnd | BinaryOp::LogicalOr | BinaryOp::NullishCoalescing)
+    }
+
+    fn _flatten_logical<'e>(bin_expr: &'e BinExpr, operators: &mut Vec<BinaryOp>, operands: &mut Vec<&'e Expr>) {
+        for (index, side) in [&bin_expr.left, &bin_expr.right].into_iter().enumerate() {
+            match side.unwrap_parens() {
+                Expr::Bin(inner) if _is_logical(inner.op) => _flatten_logical(inner, operators, operands),
+                operand => operands.push(operand),
+            }
+            if index == 0 {
+                operators.push(bin_expr.op);
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use crate::fixtures::fixtures::{analyze, first_function, parse};
+        use super::measure;
+
+        fn complexity_of(source: &str) -> usize {
+            let module = parse(source);
+            let fn_decl = first_function(&module);
+            measure(Some(fn_decl.ident.sym.as_ref()), fn_decl.function.body.as_ref().unwrap())
+        }
+
+        #[test]
+        fn straight_line_code_has_no_complexity() {
+            assert_eq!(complexity_of("function f(a) { const b = a + 1; return g(b); }"), 0);
+        }
+
+        #[test]
+        fn nesting_raises_each_increment() {
+            // 1 for the if, 2 for the for inside it, 3 for the while inside both
+            assert_eq!(complexity_of("function f(a, b) { if (a) { for (;;) { while (b) {} } } }"), 6);
+            assert_eq!(complexity_of("function f(a) { for (const x of a) { if (x) {} } }"), 3);
+            assert_eq!(complexity_of("function f(a) { if (a) { return a ? 1 : 2; } }"), 3);
+        }
+
+        #[test]
+        fn sequential_structures_do_not_nest() {
+            assert_eq!(complexity_of("function f(a, b) { if (a) {} if (b) {} while (a) {} }"), 3);
+        }
+
+        #[test]
+        fn else_if_and_else_add_flat_increments() {
+            assert_eq!(complexity_of("function f(a, b) { if (a) {} else if (b) {} else {} }"), 3);
+            // Nested in a loop only the `if` pays for the nesting
+            assert_eq!(complexity_of("function f(a, b) { for (;;) { if (a) {} else if (b) {} else {} } }"), 5);
+        }
+
+        #[test]
+        fn code_inside_else_is_nested() {
+            assert_eq!(complexity_of("function f(a, b) { if (a) {} else { if (b) {} } }"), 4);
+            assert_eq!(complexity_of("function f(a, b) { if (a) {} else if (b) { while (a) {} } }"), 4);
+        }
+
+        #[test]
+        fn catch_counts_and_nests_but_try_and_finally_do_not() {
+            assert_eq!(complexity_of("function f(a) { try { g(); } catch (e) { if (e) {} } finally { g(); } }"), 3);
+            assert_eq!(complexity_of("function f(a) { try { if (a) {} } finally {} }"), 1);
+            assert_eq!(complexity_of("function f(a) { for (;;) { try { g(); } catch { } } }"), 3);
+        }
+
+        #[test]
+        fn labeled_jumps_count_once() {
+            assert_eq!(complexity_of("function f(a) { for (;;) { if (a) break; continue; } }"), 3);
+            let labeled = "function f(a) { outer: for (;;) { for (;;) { if (a) continue outer; break outer; } } }";
+            // 1 + 2 + 3 for the structures, 1 for each labeled jump whatever its nesting
+            assert_eq!(complexity_of(labeled), 8);
+        }
+
+        #[test]
+        fn direct_recursion_counts_each_call() {
+            assert_eq!(complexity_of("function f(n) { return f(n - 1); }"), 1);
+            assert_eq!(complexity_of("function f(n) { return f(n - 1) + f(n - 2); }"), 2);
+            assert_eq!(complexity_of("function f(n) { return g(n); }"), 0);
+        }
+
+        #[test]
+        fn each_run_of_a_logical_operator_counts_once() {
+            assert_eq!(complexity_of("function f(a, b, c) { return a && b && c; }"), 1);
+            assert_eq!(complexity_of("function f(a, b, c) { return a ?? b ?? c; }"), 1);
+            assert_eq!(complexity_of("function f(a, b, c) { return a && b || c; }"), 2);
+            assert_eq!(complexity_of("function f(a, b, c) { return a || b && c; }"), 2);
+            assert_eq!(complexity_of("function f(a, b, c, d) { return a && b || c && d; }"), 3);
+            assert_eq!(complexity_of("function f(a, b, c) { return a && (b ?? c); }"), 2);
+        }
+
+        #[test]
+        fn logical_operators_in_conditions_add_to_the_structure() {
+            assert_eq!(complexity_of("function f(a, b) { if (a && b) {} }"), 2);
+            // Operands are still visited, the nested run counts on its own
+            assert_eq!(complexity_of("function f(a, b) { return a && g(b || a); }"), 2);
+        }
+
+        #[test]
+        fn nested_functions_are_measured_on_their_own() {
+            let functions = analyze(
+                "function outer(items) {\n\
+                   const check = (x) => { if (x) { return x ? 1 : 2; } };\n\
+                   items.forEach(function (item) { while (item) {} });\n\
+                   if (items) {}\n\
+                 }",
+            );
+            let complexities: Vec<(&str, usize)> = functions.iter()
+                .map(|function| (function.display_name(), function.cognitive_complexity))
+                .collect();
+            // Their bodies start again without nesting
+            assert_eq!(complexities, vec![("outer", 1), ("check", 3), ("<anonymous>", 1)]);
+        }
+    }
+}
@@ -1,35 +1,104 @@
 pub mod cyclomatic_complexity {
-    use swc_ecma_ast::{BinaryOp, Expr, Stmt};
-    use swc_ecma_visit::VisitWith;
+    use swc_ecma_ast::{
+        ArrowExpr, AssignExpr, AssignOp, BinExpr, BinaryOp, CatchClause, CondExpr, DoWhileStmt, Expr, ForInStmt,
+        ForOfStmt, ForStmt, Function, IfStmt, OptChainExpr, SwitchCase, WhileStmt,
+    };
+    use swc_ecma_visit::{Visit, VisitWith};
     use crate::visitor::FunctionAnalysisVisitor;
 
-    pub fn analyze_statement(stmt: &Stmt, complexity: &mut usize) {
-        match stmt {
-            Stmt::If(_) => {
-                *complexity += 1;
-            },
-            Stmt::Switch(switch_stmt) => {
-                *complexity += switch_stmt.cases.len();
-            },
-            Stmt::For(_) | Stmt::While(_) | Stmt::DoWhile(_) => {
-                *complexity += 1;
-            },
-            _ => {}
+    /// McCabe complexity of a single function body: 1 plus one for every decision point
+    /// (`if`, loops, non-default `case`, `catch`, `?:`, `&&`, `||`, `??`, `?.` and logical assignments).
+    pub fn measure<B: VisitWith<CyclomaticComplexityVisitor>>(body: &B) -> usize {
+        let mut visitor = CyclomaticComplexityVisitor { complexity: 1 };
+        body.visit_with(&mut visitor);
+        visitor.complexity
+    }
+
+    pub struct CyclomaticComplexityVisitor {
+        complexity: usize,
+    }
+
+    impl Visit for CyclomaticComplexityVisitor {
+        // Nested functions are measured on their own
+        fn visit_function(&mut self, _: &Function) {}
+        fn visit_arrow_expr(&mut self, _: &ArrowExpr) {}
+
+        fn visit_if_stmt(&mut self, n: &IfStmt) {
+            // `else if` is a nested `IfStmt` and is counted on its own
+            self.complexity += 1;
+            n.visit_children_with(self);
+        }
+
+        fn visit_switch_case(&mut self, n: &SwitchCase) {
+            // `default:` is the fall-through path, not an extra branch
+            if n.test.is_some() {
+                self.complexity += 1;
+            }
+            n.visit_children_with(self);
+        }
+
+        fn visit_for_stmt(&mut self, n: &ForStmt) {
+            self.complexity += 1;
+            n.visit_children_with(self);
+        }
+
+        fn visit_for_in_stmt(&mut self, n: &ForInStmt) {
+            self.complexity += 1;
+            n.visit_children_with(self);
+        }
+
+        fn visit_for_of_stmt(&mut self, n: &ForOfStmt) {
+            self.complexity += 1;
+            n.visit_children_with(self);
+        }
+
+        fn visit_while_stmt(&mut self, n: &WhileStmt) {
+            self.complexity += 1;
+            n.visit_children_with(self);
+        }
+
+        fn visit_do_while_stmt(&mut self, n: &DoWhileStmt) {
+            self.complexity += 1;
+            n.visit_children_with(self);
+        }
+
+        fn visit_catch_clause(&mut self, n: &CatchClause) {
+            self.complexity += 1;
+            n.visit_children_with(self);
+        }
+
+        fn visit_cond_expr(&mut self, n: &CondExpr) {
+            self.complexity += 1;
+            n.visit_children_with(self);
+        }
+
+        fn visit_bin_expr(&mut self, n: &BinExpr) {
+            if matches!(n.op, BinaryOp::LogicalAnd | BinaryOp::LogicalOr | BinaryOp::NullishCoalescing) {
+                self.complexity += 1;
+            }
+            n.visit_children_with(self);
+        }
+
+        fn visit_assign_expr(&mut self, n: &AssignExpr) {
+            if matches!(n.op, AssignOp::AndAssign | AssignOp::OrAssign | AssignOp::NullishAssign) {
+                self.complexity += 1;
+            }
+            n.visit_children_with(self);
+        }
+
+        fn visit_opt_chain_expr(&mut self, n: &OptChainExpr) {
+            if n.optional {
+                self.complexity += 1;
+            }
+            n.visit_children_with(self);
         }
     }
 
     pub fn analyze_expression(visitor: &mut FunctionAnalysisVisitor, expr: &Expr) {
         match expr {
             Expr::Bin(bin_expr) => {
-                // Check for logical binary operators
-                match bin_expr.op {
-                    BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul | BinaryOp::Div => {
-                        visitor.arithmetic_operations += 1;
-                    }
-                    BinaryOp::LogicalAnd | BinaryOp::LogicalOr => {
-                        visitor.cyclomatic_complexity += 1;
-                    }
-                    _ => {}
+                if let BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul | BinaryOp::Div = bin_expr.op {
+                    visitor.arithmetic_operations += 1;
                 }
 
                 // Recursively analyze the left and right operands
@@ -37,9 +106,6 @@ pub mod cyclomatic_complexity {
                 visitor.analyze_expression(&bin_expr.right);
             },
             Expr::Cond(cond_expr) => {
-                // Ternary operator represents a decision point
-                visitor.cyclomatic_complexity += 1;
-
                 // Recursively analyze the condition, consequent, and alternative of ternary operator
                 visitor.analyze_expression(&cond_expr.test);
                 visitor.analyze_expression(&cond_expr.cons);
@@ -52,4 +118,79 @@ pub mod cyclomatic_complexity {
         }
         expr.visit_children_with(visitor);
     }
-}
\ No newline at end of file
+
+    #[cfg(test)]
+    mod tests {
+        use crate::fixtures::fixtures::{first_function, parse};
+        use super::measure;
+
+        fn complexity_of(source: &str) -> usize {
+            measure(first_function(&parse(source)).function.body.as_ref().unwrap())
+        }
+
+        #[test]
+        fn straight_line_code_has_complexity_one() {
+            assert_eq!(complexity_of("function f(a) { const b = a + 1; return b; }"), 1);
+        }
+
+        #[test]
+        fn if_else_if_else_counts_each_condition() {
+            assert_eq!(complexity_of("function f(a) { if (a > 1) { return 1; } else if (a > 0) { return 2; } else { return 3; } }"), 3);
+        }
+
+        #[test]
+        fn nested_if_is_counted() {
+            assert_eq!(complexity_of("function f(a, b) { if (a) { if (b) { return 1; } } return 0; }"), 3);
+        }
+
+        #[test]
+        fn every_loop_kind_counts_once() {
+            assert_eq!(complexity_of("function f(a) { for (;;) {} }"), 2);
+            assert_eq!(complexity_of("function f(a) { for (const k in a) {} }"), 2);
+            assert_eq!(complexity_of("function f(a) { for (const v of a) {} }"), 2);
+            assert_eq!(complexity_of("function f(a) { while (a) {} }"), 2);
+            assert_eq!(complexity_of("function f(a) { do {} while (a); }"), 2);
+        }
+
+        #[test]
+        fn switch_default_case_is_not_a_branch() {
+            assert_eq!(complexity_of("function f(a) { switch (a) { case 1: return 1; case 2: return 2; default: return 0; } }"), 3);
+        }
+
+        #[test]
+        fn catch_clause_counts_once() {
+            assert_eq!(complexity_of("function f(a) { try { a(); } catch (e) { return 1; } finally { a(); } }"), 2);
+        }
+
+        #[test]
+        fn conditional_expression_counts_once() {
+            assert_eq!(complexity_of("function f(a) { return a ? 1 : 2; }"), 2);
+        }
+
+        #[test]
+        fn logical_operators_count_each_operator() {
+            assert_eq!(complexity_of("function f(a, b, c) { return a && b || c; }"), 3);
+            assert_eq!(complexity_of("function f(a, b) { return a ?? b; }"), 2);
+        }
+
+        #[test]
+        fn optional_chaining_counts_each_optional_access() {
+            assert_eq!(complexity_of("function f(a) { return a?.b.c?.(); }"), 3);
+        }
+
+        #[test]
+        fn logical_assignments_count_once() {
+            assert_eq!(complexity_of("function f(a) { a.x ||= 1; a.y &&= 2; a.z ??= 3; }"), 4);
+        }
+
+        #[test]
+        fn arithmetic_assignments_are_not_branches() {
+            assert_eq!(complexity_of("function f(a) { a += 1; a -= 2; }"), 1);
+        }
+
+        #[test]
+        fn nested_functions_are_not_counted() {
+            assert_eq!(complexity_of("function f(a) { const g = () => a ? 1 : 2; function h() { if (a) {} } return g; }"), 1);
+        }
+    }
+}
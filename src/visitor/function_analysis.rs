@@ -0,0 +1,88 @@
+pub mod function_analysis {
+    use std::path::PathBuf;
+    use swc_common::Span;
+    use crate::visitor::hotspots::hotspots::Hotspot;
+    use crate::visitor::module_links::module_links::ModuleLinks;
+    use crate::visitor::suppressions::suppressions::Suppressions;
+    use crate::visitor::type_inference::type_inference::TypeSummary;
+    use crate::visitor::typed_arrays::typed_arrays::TypedArrayUsage;
+
+    #[derive(Clone, Debug, Default, PartialEq)]
+    pub enum Recursion {
+        #[default]
+        None,
+        Direct,
+        /// Names of the functions taking part in the cycle
+        Mutual(Vec<String>),
+    }
+
+    /// A call made by a function, `callee` is either `name` or `object.name`.
+    #[derive(Clone, Debug)]
+    pub struct CallSite {
+        pub callee: String,
+        /// Number of loops of the calling function enclosing the call
+        pub loop_depth: usize,
+    }
+
+    /// Position of a function among all analyzed modules.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    pub struct FunctionId {
+        pub module: usize,
+        pub function: usize,
+    }
+
+    /// Everything measured for a single function, collected before scoring so that
+    /// analyses spanning several functions (like the call graph) can fill in their part.
+    #[derive(Clone, Debug)]
+    pub struct FunctionAnalysis {
+        pub name: Option<String>,
+        /// Name prefixed with the names of the enclosing functions, like `outer.inner`
+        pub qualified_name: String,
+        pub span: Span,
+        /// Hash of the function's tokens, unaffected by formatting and comments
+        pub body_hash: u64,
+        pub cyclomatic_complexity: usize,
+        pub cognitive_complexity: usize,
+        pub max_loop_depth: usize,
+        pub arithmetic_operations: usize,
+        pub string_operations: usize,
+        pub type_summary: TypeSummary,
+        pub typed_array_usage: TypedArrayUsage,
+        pub callees: Vec<CallSite>,
+        /// Analyzed functions the call sites resolved to
+        pub calls: Vec<FunctionId>,
+        /// Loops and arithmetic of the body, for reports showing the source
+        pub hotspots: Vec<Hotspot>,
+        pub recursion: Recursion,
+        /// Length of the longest chain of calls into other analyzed functions
+        pub call_depth: usize,
+        /// Deepest loop nesting any analyzed caller invokes this function from
+        pub caller_loop_depth: usize,
+    }
+
+    impl FunctionAnalysis {
+        pub fn display_name(&self) -> &str {
+            self.name.as_deref().unwrap_or("<anonymous>")
+        }
+
+        /// Loop depth of the function body as seen from where it is actually invoked.
+        pub fn effective_loop_depth(&self) -> usize {
+            self.max_loop_depth + self.caller_loop_depth
+        }
+    }
+
+    /// The functions of one source file together with its imports, exports and suppression comments.
+    #[derive(Clone)]
+    pub struct ModuleAnalysis {
+        pub path: PathBuf,
+        pub functions: Vec<FunctionAnalysis>,
+        pub links: ModuleLinks,
+        pub suppressions: Suppressions,
+    }
+
+    impl ModuleAnalysis {
+        pub fn function(modules: &[ModuleAnalysis], id: FunctionId) -> &FunctionAnalysis {
+            &modules[id.module].functions[id.function]
+        }
+    }
+}
@@ -0,0 +1,103 @@
+pub mod hotspots {
+    use swc_common::{Span, Spanned};
+    use swc_ecma_ast::{
+        ArrowExpr, AssignExpr, AssignOp, BinExpr, BinaryOp, DoWhileStmt, ForInStmt, ForOfStmt, ForStmt, Function,
+        UpdateExpr, WhileStmt,
+    };
+    use swc_ecma_visit::{Visit, VisitWith};
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub enum HotspotKind {
+        /// Head of a loop, from its keyword up to the body
+        Loop,
+        /// Arithmetic expression or compound assignment
+        Arithmetic,
+    }
+
+    /// Source range of a function body worth pointing at when looking at its code.
+    #[derive(Clone, Copy, Debug)]
+    pub struct Hotspot {
+        pub kind: HotspotKind,
+        pub span: Span,
+    }
+
+    pub fn collect_hotspots<B: VisitWith<HotspotVisitor>>(body: &B) -> Vec<Hotspot> {
+        let mut visitor = HotspotVisitor::default();
+        body.visit_with(&mut visitor);
+        visitor.hotspots
+    }
+
+    #[derive(Default)]
+    pub struct HotspotVisitor {
+        hotspots: Vec<Hotspot>,
+    }
+
+    impl HotspotVisitor {
+        fn add_loop(&mut self, loop_span: Span, body: &impl Spanned) {
+            let span = loop_span.with_hi(body.span().lo.max(loop_span.lo));
+            self.hotspots.push(Hotspot { kind: HotspotKind::Loop, span });
+        }
+
+        fn add_arithmetic(&mut self, span: Span) {
+            self.hotspots.push(Hotspot { kind: HotspotKind::Arithmetic, span });
+        }
+    }
+
+    impl Visit for HotspotVisitor {
+        // Nested functions have hotspots of their own
+        fn visit_function(&mut self, _: &Function) {}
+        fn visit_arrow_expr(&mut self, _: &ArrowExpr) {}
+
+        fn visit_for_stmt(&mut self, n: &ForStmt) {
+            self.add_loop(n.span, &n.body);
+            n.visit_children_with(self);
+        }
+
+        fn visit_for_in_stmt(&mut self, n: &ForInStmt) {
+            self.add_loop(n.span, &n.body);
+            n.visit_children_with(self);
+        }
+
+        fn visit_for_of_stmt(&mut self, n: &ForOfStmt) {
+            self.add_loop(n.span, &n.body);
+            n.visit_children_with(self);
+        }
+
+        fn visit_while_stmt(&mut self, n: &WhileStmt) {
+            self.add_loop(n.span, &n.body);
+            n.visit_children_with(self);
+        }
+
+        fn visit_do_while_stmt(&mut self, n: &DoWhileStmt) {
+            // `do` is all there is before the body
+            self.add_loop(n.span, &n.body);
+            n.visit_children_with(self);
+        }
+
+        fn visit_bin_expr(&mut self, n: &BinExpr) {
+            if matches!(
+                n.op,
+                BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul | BinaryOp::Div | BinaryOp::Mod | BinaryOp::Exp
+            ) {
+                self.add_arithmetic(n.span);
+            }
+            n.visit_children_with(self);
+        }
+
+        fn visit_assign_expr(&mut self, n: &AssignExpr) {
+            if matches!(
+                n.op,
+                AssignOp::AddAssign | AssignOp::SubAssign | AssignOp::MulAssign | AssignOp::DivAssign
+                    | AssignOp::ModAssign | AssignOp::ExpAssign
+            ) {
+                self.add_arithmetic(n.span);
+            }
+            n.visit_children_with(self);
+        }
+
+        fn visit_update_expr(&mut self, n: &UpdateExpr) {
+            self.add_arithmetic(n.span);
+            n.visit_children_with(self);
+        }
+    }
+}
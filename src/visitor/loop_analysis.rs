@@ -2,7 +2,7 @@ pub mod loop_analysis {
     use swc_ecma_ast::Stmt;
     use crate::visitor::FunctionAnalysisVisitor;
 
-    pub fn extract_loop_body(stmt: &Stmt) -> Option<&Box<Stmt>> {
+    pub fn extract_loop_body(stmt: &Stmt) -> Option<&Stmt> {
         match stmt {
             Stmt::For(for_stmt) => Some(&for_stmt.body),
             Stmt::While(while_stmt) => Some(&while_stmt.body),
@@ -11,7 +11,7 @@ pub mod loop_analysis {
         }
     }
 
-    pub fn handle_loop(visitor: &mut FunctionAnalysisVisitor, body: &Box<Stmt>) {
+    pub fn handle_loop(visitor: &mut FunctionAnalysisVisitor, body: &Stmt) {
         visitor.current_loop_depth += 1;
         visitor.max_loop_depth = visitor.max_loop_depth.max(visitor.current_loop_depth);
 
@@ -20,8 +20,8 @@ pub mod loop_analysis {
         visitor.current_loop_depth -= 1;
     }
 
-    fn _process_loop_body(body: &Box<Stmt>, visitor: &mut FunctionAnalysisVisitor) {
-        if let Stmt::Block(block_stmt) = &**body {
+    fn _process_loop_body(body: &Stmt, visitor: &mut FunctionAnalysisVisitor) {
+        if let Stmt::Block(block_stmt) = body {
             for inner_stmt in &block_stmt.stmts {
                 visitor.analyze_statement(inner_stmt);
             }
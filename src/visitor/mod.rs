@@ -1,12 +1,48 @@
+#[allow(clippy::module_inception)]
 pub mod visitor;
 pub use visitor::function_analysis_visitor::FunctionAnalysisVisitor;
 
-mod loop_analysis;
-mod cyclomatic_complexity;
-mod string_counter;
-mod report;
-mod scoring;
-mod max_scores;
+#[allow(clippy::module_inception)]
+pub mod function_analysis;
+pub use function_analysis::function_analysis::{FunctionAnalysis, ModuleAnalysis};
+
+#[allow(clippy::module_inception)]
+mod module_links;
+pub use module_links::module_links::{collect_module_links, ModuleLinks};
+
+#[allow(clippy::module_inception)]
+mod call_graph;
+pub use call_graph::call_graph::analyze_calls;
 
+#[allow(clippy::module_inception)]
+mod suppressions;
+pub use suppressions::suppressions::Suppressions;
 
+#[allow(clippy::module_inception)]
+mod clustering;
+pub use clustering::clustering::find_clusters;
 
+#[allow(clippy::module_inception)]
+mod scoring;
+pub use scoring::scoring::{candidate_score, top_reason};
+
+#[allow(clippy::module_inception)]
+mod normalization;
+pub use normalization::normalization::{NormalizationMode, NormalizationModel, ProjectRank};
+
+#[allow(clippy::module_inception)]
+mod loop_analysis;
+#[allow(clippy::module_inception)]
+mod cyclomatic_complexity;
+#[allow(clippy::module_inception)]
+mod cognitive_complexity;
+#[allow(clippy::module_inception)]
+mod string_counter;
+#[allow(clippy::module_inception)]
+pub mod report;
+#[allow(clippy::module_inception)]
+mod type_inference;
+#[allow(clippy::module_inception)]
+mod typed_arrays;
+#[allow(clippy::module_inception)]
+mod hotspots;
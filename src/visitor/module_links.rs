@@ -0,0 +1,358 @@
+pub mod module_links {
+    use swc_ecma_ast::{
+        CallExpr, Callee, Decl, DefaultDecl, ExportSpecifier, Expr, ImportSpecifier, Lit, MemberExpr, MemberProp,
+        Module, ModuleDecl, ModuleExportName, ModuleItem, ObjectPatProp, Pat, PatOrExpr, Prop, PropName,
+        PropOrSpread, Stmt, VarDeclarator,
+    };
+
+    /// Module specifier as written in the source, and the index of the analyzed module it resolves to.
+    #[derive(Clone, Debug)]
+    pub struct ModuleLink {
+        pub specifier: String,
+        pub resolved: Option<usize>,
+    }
+
+    #[derive(Clone, Debug, PartialEq)]
+    pub enum ImportedName {
+        Named(String),
+        Default,
+        /// `import * as ns` or a whole `require()`d module
+        Namespace,
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct Import {
+        pub local: String,
+        pub imported: ImportedName,
+        pub source: ModuleLink,
+    }
+
+    #[derive(Clone, Debug)]
+    pub enum Export {
+        /// `export { local as exported }`, `export function exported`, `module.exports.exported = local`
+        Local { exported: String, local: String },
+        /// `export { imported as exported } from 'source'`
+        Reexport { exported: String, imported: String, source: ModuleLink },
+        /// `export * from 'source'`
+        ReexportAll { source: ModuleLink },
+    }
+
+    pub const DEFAULT_EXPORT: &str = "default";
+
+    #[derive(Clone, Debug, Default)]
+    pub struct ModuleLinks {
+        pub imports: Vec<Import>,
+        pub exports: Vec<Export>,
+    }
+
+    impl ModuleLinks {
+        pub fn sources_mut(&mut self) -> impl Iterator<Item = &mut ModuleLink> {
+            let import_sources = self.imports.iter_mut().map(|import| &mut import.source);
+            let export_sources = self.exports.iter_mut().filter_map(|export| match export {
+                Export::Local { .. } => None,
+                Export::Reexport { source, .. } | Export::ReexportAll { source } => Some(source),
+            });
+            import_sources.chain(export_sources)
+        }
+    }
+
+    /// Collects the top-level ES module and CommonJS imports and exports of a module.
+    pub fn collect_module_links(module: &Module) -> ModuleLinks {
+        let mut links = ModuleLinks::default();
+        for item in &module.body {
+            match item {
+                ModuleItem::ModuleDecl(module_decl) => _collect_module_decl(module_decl, &mut links),
+                ModuleItem::Stmt(Stmt::Decl(Decl::Var(var_decl))) => {
+                    for declarator in &var_decl.decls {
+                        _collect_require(declarator, &mut links);
+                    }
+                }
+                ModuleItem::Stmt(Stmt::Expr(expr_stmt)) => _collect_commonjs_export(&expr_stmt.expr, &mut links),
+                _ => {}
+            }
+        }
+        links
+    }
+
+    fn _collect_module_decl(module_decl: &ModuleDecl, links: &mut ModuleLinks) {
+        match module_decl {
+            ModuleDecl::Import(import_decl) => {
+                let source = _link(&import_decl.src.value);
+                for specifier in &import_decl.specifiers {
+                    let (local, imported) = match specifier {
+                        ImportSpecifier::Named(named) => {
+                            let imported = named.imported.as_ref().map_or_else(|| named.local.sym.to_string(), _export_name);
+                            (named.local.sym.to_string(), ImportedName::Named(imported))
+                        }
+                        ImportSpecifier::Default(default) => (default.local.sym.to_string(), ImportedName::Default),
+                        ImportSpecifier::Namespace(namespace) => (namespace.local.sym.to_string(), ImportedName::Namespace),
+                    };
+                    links.imports.push(Import { local, imported, source: source.clone() });
+                }
+            }
+            ModuleDecl::ExportDecl(export_decl) => match &export_decl.decl {
+                Decl::Fn(fn_decl) => _export_local(links, fn_decl.ident.sym.as_ref(), fn_decl.ident.sym.as_ref()),
+                Decl::Var(var_decl) => {
+                    for declarator in &var_decl.decls {
+                        if let Pat::Ident(binding) = &declarator.name {
+                            _export_local(links, binding.id.sym.as_ref(), binding.id.sym.as_ref());
+                        }
+                    }
+                }
+                _ => {}
+            },
+            ModuleDecl::ExportNamed(named_export) => {
+                for specifier in &named_export.specifiers {
+                    let (exported, orig) = match specifier {
+                        ExportSpecifier::Named(named) => {
+                            let orig = _export_name(&named.orig);
+                            (named.exported.as_ref().map_or_else(|| orig.clone(), _export_name), orig)
+                        }
+                        ExportSpecifier::Default(default) => (default.exported.sym.to_string(), DEFAULT_EXPORT.to_string()),
+                        // `export * as ns from` cannot be called directly
+                        ExportSpecifier::Namespace(_) => continue,
+                    };
+                    match &named_export.src {
+                        Some(src) => links.exports.push(Export::Reexport { exported, imported: orig, source: _link(&src.value) }),
+                        None => links.exports.push(Export::Local { exported, local: orig }),
+                    }
+                }
+            }
+            ModuleDecl::ExportDefaultDecl(default_decl) => {
+                if let DefaultDecl::Fn(fn_expr) = &default_decl.decl {
+                    if let Some(ident) = &fn_expr.ident {
+                        _export_local(links, DEFAULT_EXPORT, ident.sym.as_ref());
+                    }
+                }
+            }
+            ModuleDecl::ExportDefaultExpr(default_expr) => {
+                if let Expr::Ident(ident) = default_expr.expr.unwrap_parens() {
+                    _export_local(links, DEFAULT_EXPORT, ident.sym.as_ref());
+                }
+            }
+            ModuleDecl::ExportAll(export_all) => links.exports.push(Export::ReexportAll { source: _link(&export_all.src.value) }),
+            _ => {}
+        }
+    }
+
+    /// `const x = require('...')` and `const { a, b: c } = require('...')`
+    fn _collect_require(declarator: &VarDeclarator, links: &mut ModuleLinks) {
+        let Some(specifier) = declarator.init.as_deref().and_then(_require_specifier) else {
+            return;
+        };
+        let source = _link(&specifier);
+        match &declarator.name {
+            Pat::Ident(binding) => links.imports.push(Import {
+                local: binding.id.sym.to_string(),
+                imported: ImportedName::Namespace,
+                source,
+            }),
+            Pat::Object(object_pat) => {
+                for prop in &object_pat.props {
+                    let (local, imported) = match prop {
+                        ObjectPatProp::Assign(assign) => (assign.key.sym.to_string(), assign.key.sym.to_string()),
+                        ObjectPatProp::KeyValue(key_value) => match (&key_value.key, &*key_value.value) {
+                            (PropName::Ident(key), Pat::Ident(value)) => (value.id.sym.to_string(), key.sym.to_string()),
+                            _ => continue,
+                        },
+                        ObjectPatProp::Rest(_) => continue,
+                    };
+                    links.imports.push(Import { local, imported: ImportedName::Named(imported), source: source.clone() });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn _require_specifier(expr: &Expr) -> Option<String> {
+        let Expr::Call(CallExpr { callee: Callee::Expr(callee), args, .. }) = expr.unwrap_parens() else {
+            return None;
+        };
+        if !matches!(&**callee, Expr::Ident(ident) if ident.sym.as_ref() == "require") {
+            return None;
+        }
+        match args.first().map(|arg| &*arg.expr) {
+            Some(Expr::Lit(Lit::Str(specifier))) => Some(specifier.value.to_string()),
+            _ => None,
+        }
+    }
+
+    /// `module.exports = ...`, `module.exports.name = ...` and `exports.name = ...`
+    fn _collect_commonjs_export(expr: &Expr, links: &mut ModuleLinks) {
+        let Expr::Assign(assign_expr) = expr else {
+            return;
+        };
+        let target = match &assign_expr.left {
+            PatOrExpr::Expr(expr) => &**expr,
+            PatOrExpr::Pat(pat) => match &**pat {
+                Pat::Expr(expr) => &**expr,
+                _ => return,
+            },
+        };
+        let Expr::Member(member) = target else {
+            return;
+        };
+
+        if _is_module_exports(member) {
+            match assign_expr.right.unwrap_parens() {
+                Expr::Ident(ident) => _export_local(links, DEFAULT_EXPORT, ident.sym.as_ref()),
+                Expr::Object(object) => {
+                    for prop in &object.props {
+                        match prop {
+                            PropOrSpread::Prop(prop) => match &**prop {
+                                Prop::Shorthand(ident) => _export_local(links, ident.sym.as_ref(), ident.sym.as_ref()),
+                                Prop::KeyValue(key_value) => {
+                                    if let PropName::Ident(key) = &key_value.key {
+                                        // Functions defined inline are named after their key
+                                        let local = match key_value.value.unwrap_parens() {
+                                            Expr::Ident(value) => value.sym.as_ref(),
+                                            _ => key.sym.as_ref(),
+                                        };
+                                        _export_local(links, key.sym.as_ref(), local);
+                                    }
+                                }
+                                _ => {}
+                            },
+                            PropOrSpread::Spread(_) => {}
+                        }
+                    }
+                }
+                _ => {}
+            }
+            return;
+        }
+
+        let is_exports_object = match member.obj.unwrap_parens() {
+            Expr::Ident(ident) => ident.sym.as_ref() == "exports",
+            Expr::Member(inner) => _is_module_exports(inner),
+            _ => false,
+        };
+        if let (true, MemberProp::Ident(prop)) = (is_exports_object, &member.prop) {
+            let local = match assign_expr.right.unwrap_parens() {
+                Expr::Ident(value) => value.sym.as_ref(),
+                _ => prop.sym.as_ref(),
+            };
+            _export_local(links, prop.sym.as_ref(), local);
+        }
+    }
+
+    fn _is_module_exports(member: &MemberExpr) -> bool {
+        matches!(member.obj.unwrap_parens(), Expr::Ident(obj) if obj.sym.as_ref() == "module")
+            && matches!(&member.prop, MemberProp::Ident(prop) if prop.sym.as_ref() == "exports")
+    }
+
+    fn _export_local(links: &mut ModuleLinks, exported: &str, local: &str) {
+        links.exports.push(Export::Local { exported: exported.to_string(), local: local.to_string() });
+    }
+
+    fn _export_name(name: &ModuleExportName) -> String {
+        match name {
+            ModuleExportName::Ident(ident) => ident.sym.to_string(),
+            ModuleExportName::Str(str) => str.value.to_string(),
+        }
+    }
+
+    fn _link(specifier: &str) -> ModuleLink {
+        ModuleLink { specifier: specifier.to_string(), resolved: None }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use crate::fixtures::fixtures::parse;
+        use super::{collect_module_links, Export, ImportedName, ModuleLinks};
+
+        fn links_of(source: &str) -> ModuleLinks {
+            collect_module_links(&parse(source))
+        }
+
+        fn imports(links: &ModuleLinks) -> Vec<(&str, ImportedName, &str)> {
+            links.imports.iter()
+                .map(|import| (import.local.as_str(), import.imported.clone(), import.source.specifier.as_str()))
+                .collect()
+        }
+
+        /// Exports as `exported=local`, `exported=source:imported` and `*=source`.
+        fn exports(links: &ModuleLinks) -> Vec<String> {
+            links.exports.iter()
+                .map(|export| match export {
+                    Export::Local { exported, local } => format!("{}={}", exported, local),
+                    Export::Reexport { exported, imported, source } => format!("{}={}:{}", exported, source.specifier, imported),
+                    Export::ReexportAll { source } => format!("*={}", source.specifier),
+                })
+                .collect()
+        }
+
+        #[test]
+        fn es_imports_keep_local_and_imported_names() {
+            let links = links_of("import mix, { clamp, lerp as blend } from './math'; import * as geo from './geo';");
+            assert_eq!(imports(&links), vec![
+                ("mix", ImportedName::Default, "./math"),
+                ("clamp", ImportedName::Named("clamp".to_string()), "./math"),
+                ("blend", ImportedName::Named("lerp".to_string()), "./math"),
+                ("geo", ImportedName::Namespace, "./geo"),
+            ]);
+        }
+
+        #[test]
+        fn es_exports_map_exported_to_local_names() {
+            let links = links_of(
+                "export function render() {}\n\
+                 export const scale = () => 1;\n\
+                 function blur() {}\n\
+                 export { blur as smooth };\n\
+                 export default blur;",
+            );
+            assert_eq!(exports(&links), vec!["render=render", "scale=scale", "smooth=blur", "default=blur"]);
+        }
+
+        #[test]
+        fn named_default_function_is_the_default_export() {
+            let links = links_of("export default function main() {}");
+            assert_eq!(exports(&links), vec!["default=main"]);
+        }
+
+        #[test]
+        fn reexports_keep_their_source() {
+            let links = links_of(
+                "export { mix, lerp as blend } from './math';\n\
+                 export * from './geo';\n\
+                 export * as shapes from './shapes';",
+            );
+            assert_eq!(exports(&links), vec!["mix=./math:mix", "blend=./math:lerp", "*=./geo"]);
+        }
+
+        #[test]
+        fn require_binds_a_namespace_or_destructured_names() {
+            let links = links_of("const math = require('./math');\nconst { clamp, lerp: blend, ...rest } = require('./utils');");
+            assert_eq!(imports(&links), vec![
+                ("math", ImportedName::Namespace, "./math"),
+                ("clamp", ImportedName::Named("clamp".to_string()), "./utils"),
+                ("blend", ImportedName::Named("lerp".to_string()), "./utils"),
+            ]);
+        }
+
+        #[test]
+        fn require_of_a_computed_specifier_is_ignored() {
+            let links = links_of("const math = require(name); const other = load('./other');");
+            assert!(links.imports.is_empty());
+        }
+
+        #[test]
+        fn module_exports_of_an_identifier_is_the_default_export() {
+            let links = links_of("function main() {}\nmodule.exports = main;");
+            assert_eq!(exports(&links), vec!["default=main"]);
+        }
+
+        #[test]
+        fn module_exports_object_exports_each_key() {
+            let links = links_of("function mix() {}\nfunction blur() {}\nmodule.exports = { mix, smooth: blur, scale: function () {} };");
+            assert_eq!(exports(&links), vec!["mix=mix", "smooth=blur", "scale=scale"]);
+        }
+
+        #[test]
+        fn exports_properties_are_named_exports() {
+            let links = links_of("function mix() {}\nexports.mix = mix;\nmodule.exports.scale = () => 1;\nother.exports.x = mix;");
+            assert_eq!(exports(&links), vec!["mix=mix", "scale=scale"]);
+        }
+    }
+}
@@ -0,0 +1,248 @@
+pub mod normalization {
+    use std::fmt;
+    use std::str::FromStr;
+    use crate::Metrics;
+    use crate::visitor::FunctionAnalysis;
+    use crate::visitor::scoring::scoring::{max_raw_score, raw_score};
+
+    /// Bumped whenever the same inputs would produce different normalized scores,
+    /// scores of different versions (for example in a baseline) are not comparable.
+    pub const MODEL_VERSION: u32 = 1;
+
+    // Keeps configurations with very low or penalty-dominated thresholds from dividing by zero or flipping signs
+    const MIN_MAX_RAW_SCORE: f32 = 1.0;
+
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub enum NormalizationMode {
+        /// Scored against the default thresholds, comparable between configurations
+        Absolute,
+        /// Scored against the active thresholds
+        #[default]
+        ThresholdRelative,
+        /// Position of the function among all functions of the project
+        ProjectPercentile,
+    }
+
+    impl NormalizationMode {
+        pub const NAMES: [&'static str; 3] = ["absolute", "threshold-relative", "project-percentile"];
+    }
+
+    impl fmt::Display for NormalizationMode {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            let name = match self {
+                NormalizationMode::Absolute => Self::NAMES[0],
+                NormalizationMode::ThresholdRelative => Self::NAMES[1],
+                NormalizationMode::ProjectPercentile => Self::NAMES[2],
+            };
+            write!(f, "{}", name)
+        }
+    }
+
+    impl FromStr for NormalizationMode {
+        type Err = String;
+
+        fn from_str(name: &str) -> Result<Self, Self::Err> {
+            match name {
+                "absolute" => Ok(NormalizationMode::Absolute),
+                "threshold-relative" => Ok(NormalizationMode::ThresholdRelative),
+                "project-percentile" => Ok(NormalizationMode::ProjectPercentile),
+                _ => Err(format!("unknown normalization '{}', expected one of: {}", name, Self::NAMES.join(", "))),
+            }
+        }
+    }
+
+    /// Position of a function among all analyzed functions.
+    #[derive(Clone, Copy, Debug)]
+    pub struct ProjectRank {
+        /// Share of functions scoring the same or lower, from 0 to 100
+        pub percentile: f32,
+        /// Share of functions scoring the same or higher, from 0 to 100
+        pub top_share: f32,
+    }
+
+    /// Maps raw function scores to the 0-10 scale, derived only from the active configuration
+    /// and, in the percentile mode, from the analyzed functions themselves.
+    pub struct NormalizationModel {
+        pub mode: NormalizationMode,
+        // Thresholds the raw scores are computed against
+        reference_thresholds: Metrics,
+        max_raw_score: f32,
+        // Raw scores of every analyzed function in ascending order
+        project_scores: Vec<f32>,
+    }
+
+    impl NormalizationModel {
+        pub fn new<'f>(
+            mode: NormalizationMode,
+            thresholds: &Metrics,
+            functions: impl IntoIterator<Item = &'f FunctionAnalysis>,
+        ) -> Self {
+            let reference_thresholds = match mode {
+                NormalizationMode::Absolute => Metrics::new(),
+                _ => thresholds.clone(),
+            };
+            let mut project_scores: Vec<f32> = functions.into_iter()
+                .map(|function| raw_score(function, &reference_thresholds))
+                .collect();
+            project_scores.sort_by(f32::total_cmp);
+
+            NormalizationModel {
+                mode,
+                max_raw_score: max_raw_score(&reference_thresholds).max(MIN_MAX_RAW_SCORE),
+                reference_thresholds,
+                project_scores,
+            }
+        }
+
+        /// Identifies the model in baselines, like `threshold-relative@1`.
+        pub fn id(&self) -> String {
+            format!("{}@{}", self.mode, MODEL_VERSION)
+        }
+
+        /// Unnormalized score, finer grained than the 0-10 scale for ranking.
+        pub fn raw_score(&self, function: &FunctionAnalysis) -> f32 {
+            raw_score(function, &self.reference_thresholds)
+        }
+
+        pub fn score(&self, function: &FunctionAnalysis) -> usize {
+            let raw = self.raw_score(function);
+            let normalized = match self.mode {
+                NormalizationMode::ProjectPercentile => self.percentile(raw) / 10.0,
+                _ => scale_raw_score(raw, self.max_raw_score),
+            };
+            normalized.round() as usize
+        }
+
+        pub fn rank(&self, function: &FunctionAnalysis) -> ProjectRank {
+            let raw = self.raw_score(function);
+            let below = self.project_scores.partition_point(|&score| score < raw);
+            let total = self.project_scores.len().max(1) as f32;
+            ProjectRank {
+                percentile: self.percentile(raw),
+                top_share: (self.project_scores.len() - below) as f32 / total * 100.0,
+            }
+        }
+
+        /// Share of the project's functions scoring at most `raw`, from 0 to 100.
+        pub fn percentile(&self, raw: f32) -> f32 {
+            if self.project_scores.is_empty() {
+                return 0.0;
+            }
+            let at_or_below = self.project_scores.partition_point(|&score| score <= raw);
+            at_or_below as f32 / self.project_scores.len() as f32 * 100.0
+        }
+    }
+
+    /// Raw score relative to the maximum, on the 0-10 scale.
+    pub fn scale_raw_score(raw: f32, max_raw_score: f32) -> f32 {
+        let scaled = raw / max_raw_score.max(MIN_MAX_RAW_SCORE) * 10.0;
+        if scaled.is_finite() { scaled.clamp(0.0, 10.0) } else { 0.0 }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use crate::Metrics;
+        use crate::fixtures::fixtures::{analyze, ZERO_THRESHOLDS};
+        use crate::visitor::FunctionAnalysis;
+        use crate::visitor::scoring::scoring::{max_raw_score, raw_score};
+        use super::{scale_raw_score, NormalizationMode, NormalizationModel, MODEL_VERSION};
+
+        // Raw scores 0.5, 3 and 6 against zero thresholds
+        const THREE_FUNCTIONS: &str = "function flat() { return g(); }\n\
+                                       function looped() { for (;;) {} }\n\
+                                       function nested() { for (;;) { for (;;) {} } }";
+
+        fn scores(model: &NormalizationModel, functions: &[FunctionAnalysis]) -> Vec<usize> {
+            functions.iter().map(|function| model.score(function)).collect()
+        }
+
+        #[test]
+        fn raw_scores_scale_to_the_0_10_range() {
+            assert_eq!(scale_raw_score(0.0, 4.0), 0.0);
+            assert_eq!(scale_raw_score(2.0, 4.0), 5.0);
+            assert_eq!(scale_raw_score(8.0, 4.0), 10.0);
+            assert_eq!(scale_raw_score(-3.0, 4.0), 0.0);
+        }
+
+        #[test]
+        fn degenerate_maximum_never_divides_by_zero_or_flips_the_sign() {
+            // Zero and negative maximums fall back to the minimum of 1
+            assert_eq!(scale_raw_score(0.5, 0.0), 5.0);
+            assert_eq!(scale_raw_score(0.5, -20.0), 5.0);
+            assert_eq!(scale_raw_score(-0.5, -20.0), 0.0);
+            assert_eq!(scale_raw_score(f32::NAN, 4.0), 0.0);
+            assert_eq!(scale_raw_score(f32::INFINITY, 4.0), 0.0);
+        }
+
+        #[test]
+        fn penalty_dominated_thresholds_keep_scores_in_range() {
+            let thresholds = Metrics { string_operations: 100, ..ZERO_THRESHOLDS };
+            assert!(max_raw_score(&thresholds) < 0.0);
+            let functions = analyze(THREE_FUNCTIONS);
+            let model = NormalizationModel::new(NormalizationMode::ThresholdRelative, &thresholds, &functions);
+            assert_eq!(scores(&model, &functions), vec![5, 10, 10]);
+        }
+
+        #[test]
+        fn threshold_relative_scores_against_the_active_thresholds() {
+            let functions = analyze(THREE_FUNCTIONS);
+            let model = NormalizationModel::new(NormalizationMode::ThresholdRelative, &ZERO_THRESHOLDS, &functions);
+            assert_eq!(max_raw_score(&ZERO_THRESHOLDS), 4.0);
+            assert_eq!(functions.iter().map(|function| raw_score(function, &ZERO_THRESHOLDS)).collect::<Vec<_>>(), vec![0.5, 3.0, 6.0]);
+            assert_eq!(scores(&model, &functions), vec![1, 8, 10]);
+        }
+
+        #[test]
+        fn absolute_scores_ignore_the_active_thresholds() {
+            let functions = analyze(THREE_FUNCTIONS);
+            let with_defaults = NormalizationModel::new(NormalizationMode::Absolute, &Metrics::new(), &functions);
+            let with_zeros = NormalizationModel::new(NormalizationMode::Absolute, &ZERO_THRESHOLDS, &functions);
+            assert_eq!(scores(&with_defaults, &functions), scores(&with_zeros, &functions));
+            let relative = NormalizationModel::new(NormalizationMode::ThresholdRelative, &ZERO_THRESHOLDS, &functions);
+            assert_ne!(scores(&with_zeros, &functions), scores(&relative, &functions));
+        }
+
+        #[test]
+        fn project_percentile_scores_by_position() {
+            let functions = analyze(THREE_FUNCTIONS);
+            let model = NormalizationModel::new(NormalizationMode::ProjectPercentile, &ZERO_THRESHOLDS, &functions);
+            // 33%, 67% and 100% of the functions score the same or lower
+            assert_eq!(scores(&model, &functions), vec![3, 7, 10]);
+            let top_shares: Vec<f32> = functions.iter().map(|function| model.rank(function).top_share.round()).collect();
+            assert_eq!(top_shares, vec![100.0, 67.0, 33.0]);
+        }
+
+        #[test]
+        fn equal_scores_share_the_top_percentile() {
+            let functions = analyze("function a() {}\nfunction b() {}\nfunction c() {}");
+            let model = NormalizationModel::new(NormalizationMode::ProjectPercentile, &ZERO_THRESHOLDS, &functions);
+            assert_eq!(scores(&model, &functions), vec![10, 10, 10]);
+            let rank = model.rank(&functions[0]);
+            assert_eq!((rank.percentile, rank.top_share), (100.0, 100.0));
+        }
+
+        #[test]
+        fn empty_project_ranks_nothing() {
+            let functions = analyze("function a() { for (;;) {} }");
+            let model = NormalizationModel::new(NormalizationMode::ProjectPercentile, &ZERO_THRESHOLDS, &[]);
+            assert_eq!(model.percentile(100.0), 0.0);
+            assert_eq!(model.score(&functions[0]), 0);
+            let rank = model.rank(&functions[0]);
+            assert_eq!((rank.percentile, rank.top_share), (0.0, 0.0));
+        }
+
+        #[test]
+        fn model_id_carries_mode_and_version() {
+            let model = NormalizationModel::new(NormalizationMode::ProjectPercentile, &ZERO_THRESHOLDS, &[]);
+            assert_eq!(model.id(), format!("project-percentile@{}", MODEL_VERSION));
+        }
+
+        #[test]
+        fn modes_parse_from_their_names() {
+            for name in NormalizationMode::NAMES {
+                assert_eq!(name.parse::<NormalizationMode>().map(|mode| mode.to_string()), Ok(name.to_string()));
+            }
+            assert!("relative".parse::<NormalizationMode>().is_err());
+        }
+    }
+}
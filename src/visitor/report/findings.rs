@@ -0,0 +1,108 @@
+pub mod findings {
+    use std::fmt;
+    use std::io::{self, Write};
+    use std::rc::Rc;
+    use std::str::FromStr;
+    use swc_common::SourceMap;
+    use crate::Metrics;
+    use crate::visitor::{FunctionAnalysis, ModuleAnalysis, ProjectRank};
+    use crate::visitor::clustering::clustering::Cluster;
+    use crate::visitor::function_analysis::function_analysis::FunctionId;
+    use crate::visitor::report::html::html::write_html_report;
+    use crate::visitor::report::markdown::markdown::write_markdown_report;
+    use crate::visitor::report::records::records::{write_csv_report, write_ndjson_report};
+    use crate::visitor::report::summary::summary::Summary;
+    use crate::visitor::report::report::report::write_text_report;
+    use crate::visitor::suppressions::suppressions::Suppression;
+
+    /// A reported function, or with `--all` any analyzed one.
+    pub struct Finding<'a> {
+        pub id: FunctionId,
+        pub function: &'a FunctionAnalysis,
+        pub score: usize,
+        pub raw_score: f32,
+        /// Position within the project, when findings are ranked
+        pub rank: Option<ProjectRank>,
+    }
+
+    /// Everything a run reports, rendered by one of the formats.
+    pub struct AnalysisReport<'a> {
+        pub modules: &'a [ModuleAnalysis],
+        /// Thresholds the findings were selected with
+        pub thresholds: &'a Metrics,
+        pub findings: Vec<Finding<'a>>,
+        /// Every analyzed function, with `--all`
+        pub functions: Option<Vec<Finding<'a>>>,
+        pub clusters: Vec<Cluster>,
+        pub unused_suppressions: Vec<&'a Suppression>,
+        /// Findings hidden by the baseline, when one is used
+        pub known_findings: Option<usize>,
+        pub summary: Summary,
+        /// Only the summary is written, not the individual findings
+        pub summary_only: bool,
+    }
+
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub enum ReportFormat {
+        #[default]
+        Text,
+        Html,
+        /// Compact table for pull request comments
+        Markdown,
+        /// One row per function with every raw metric
+        Csv,
+        Ndjson,
+    }
+
+    impl ReportFormat {
+        pub const NAMES: [&'static str; 5] = ["text", "html", "markdown", "csv", "ndjson"];
+
+        /// Formats listing every analyzed function with `--all`.
+        pub fn lists_all_functions(&self) -> bool {
+            matches!(self, ReportFormat::Csv | ReportFormat::Ndjson)
+        }
+    }
+
+    impl fmt::Display for ReportFormat {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            let name = match self {
+                ReportFormat::Text => Self::NAMES[0],
+                ReportFormat::Html => Self::NAMES[1],
+                ReportFormat::Markdown => Self::NAMES[2],
+                ReportFormat::Csv => Self::NAMES[3],
+                ReportFormat::Ndjson => Self::NAMES[4],
+            };
+            write!(f, "{}", name)
+        }
+    }
+
+    impl FromStr for ReportFormat {
+        type Err = String;
+
+        fn from_str(name: &str) -> Result<Self, Self::Err> {
+            match name {
+                "text" => Ok(ReportFormat::Text),
+                "html" => Ok(ReportFormat::Html),
+                "markdown" => Ok(ReportFormat::Markdown),
+                "csv" => Ok(ReportFormat::Csv),
+                "ndjson" => Ok(ReportFormat::Ndjson),
+                _ => Err(format!("unknown format '{}', expected one of: {}", name, Self::NAMES.join(", "))),
+            }
+        }
+    }
+
+    pub fn write_report(
+        report: &AnalysisReport,
+        format: ReportFormat,
+        source_map: &Rc<SourceMap>,
+        out: &mut dyn Write,
+    ) -> io::Result<()> {
+        match format {
+            ReportFormat::Text => write_text_report(report, source_map, out),
+            ReportFormat::Html => write_html_report(report, source_map, out),
+            ReportFormat::Markdown => write_markdown_report(report, source_map, out),
+            ReportFormat::Csv => write_csv_report(report, source_map, out),
+            ReportFormat::Ndjson => write_ndjson_report(report, source_map, out),
+        }
+    }
+}
@@ -0,0 +1,393 @@
+pub mod html {
+    use std::ffi::OsStr;
+    use std::io::{self, Write};
+    use std::rc::Rc;
+    use swc_common::{BytePos, FileName, SourceMap};
+    use swc_common::input::StringInput;
+    use swc_ecma_ast::EsVersion;
+    use swc_ecma_parser::{Syntax, token::{Token, Word}};
+    use swc_ecma_parser::lexer::Lexer;
+    use crate::parsing::source_blocks;
+    use crate::visitor::{FunctionAnalysis, ModuleAnalysis};
+    use crate::visitor::clustering::clustering::Cluster;
+    use crate::visitor::function_analysis::function_analysis::Recursion;
+    use crate::visitor::hotspots::hotspots::HotspotKind;
+    use crate::visitor::report::findings::findings::{AnalysisReport, Finding};
+    use crate::visitor::report::summary::summary::Aggregate;
+    use crate::visitor::report::report::report::{
+        format_aggregate, format_function_list, format_metrics, format_type_summary, CONCERN, DANGER, WARNING,
+    };
+
+    const STYLE: &str = r#"
+body { font-family: system-ui, sans-serif; margin: 2rem; color: #1f2328; }
+table { border-collapse: collapse; width: 100%; }
+th, td { border-bottom: 1px solid #d0d7de; padding: .4rem .6rem; text-align: left; }
+th { cursor: pointer; user-select: none; background: #f6f8fa; }
+th[data-order="asc"]::after { content: " \25B2"; }
+th[data-order="desc"]::after { content: " \25BC"; }
+td.number { text-align: right; font-variant-numeric: tabular-nums; }
+section.finding { margin-top: 2.5rem; }
+.score { font-weight: bold; padding: .1rem .4rem; border-radius: .3rem; }
+.band-danger { background: #ffd7d5; color: #82071e; }
+.band-warning { background: #ffe7d1; color: #953800; }
+.band-concern { background: #fff8c5; color: #7d4e00; }
+.band-low { background: #eaeef2; }
+dl { display: grid; grid-template-columns: max-content auto; gap: .2rem 1rem; }
+dt { font-weight: bold; }
+pre.source { background: #f6f8fa; padding: 1rem; overflow-x: auto; line-height: 1.4; }
+.ln { display: inline-block; width: 3em; color: #8c959f; user-select: none; }
+.tok-keyword { color: #cf222e; }
+.tok-string { color: #0a3069; }
+.tok-number { color: #0550ae; }
+.tok-comment { color: #6e7781; font-style: italic; }
+.hot-loop { background: #ddf4ff; outline: 1px solid #54aeff; }
+.hot-arithmetic { background: #fff1e5; }
+"#;
+
+    // Sorts the findings table by the clicked column, numeric columns carry `data-value`
+    const SCRIPT: &str = r#"
+document.querySelectorAll("th").forEach((header, column) => header.addEventListener("click", () => {
+  const body = header.closest("table").tBodies[0];
+  const order = header.dataset.order === "desc" ? "asc" : "desc";
+  header.closest("tr").querySelectorAll("th").forEach(other => delete other.dataset.order);
+  header.dataset.order = order;
+  const value = row => {
+    const cell = row.cells[column];
+    return cell.dataset.value !== undefined ? parseFloat(cell.dataset.value) : cell.textContent;
+  };
+  [...body.rows]
+    .sort((a, b) => (value(a) > value(b) ? 1 : value(a) < value(b) ? -1 : 0) * (order === "asc" ? 1 : -1))
+    .forEach(row => body.appendChild(row));
+}));
+"#;
+
+    /// A self-contained page with a sortable table of the findings and, per finding,
+    /// its metric breakdown and highlighted source.
+    pub fn write_html_report(report: &AnalysisReport, source_map: &Rc<SourceMap>, out: &mut dyn Write) -> io::Result<()> {
+        writeln!(out, "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">")?;
+        writeln!(out, "<title>wasm-grate report</title>\n<style>{}</style>\n</head>\n<body>", STYLE)?;
+        writeln!(out, "<h1>wasm-grate report</h1>")?;
+        writeln!(
+            out,
+            "<p>{}{}</p>",
+            _escape(&format_aggregate(&report.summary.total, true)),
+            _known_findings_note(report)
+        )?;
+
+        if !report.summary_only {
+            _write_findings(report, source_map, out)?;
+        }
+
+        writeln!(out, "<h2>Directories</h2>")?;
+        _write_aggregates(&report.summary.directories, out)?;
+        writeln!(out, "<h2>Files</h2>")?;
+        _write_aggregates(&report.summary.files, out)?;
+
+        if !report.unused_suppressions.is_empty() {
+            writeln!(out, "<h2>Unused suppressions</h2>\n<ul>")?;
+            for suppression in &report.unused_suppressions {
+                writeln!(
+                    out,
+                    "<li><code>{}</code> at {}</li>",
+                    suppression.kind.directive(),
+                    _escape(&_location(source_map, suppression.span.lo))
+                )?;
+            }
+            writeln!(out, "</ul>")?;
+        }
+
+        writeln!(out, "<script>{}</script>\n</body>\n</html>", SCRIPT)
+    }
+
+    fn _write_findings(report: &AnalysisReport, source_map: &Rc<SourceMap>, out: &mut dyn Write) -> io::Result<()> {
+        writeln!(out, "<table>\n<thead><tr>")?;
+        for header in [
+            "Function", "Location", "Score", "Cyclomatic", "Cognitive", "Loop depth", "Arithmetic", "String", "Numeric data",
+        ] {
+            writeln!(out, "<th>{}</th>", header)?;
+        }
+        writeln!(out, "</tr></thead>\n<tbody>")?;
+        for (index, finding) in report.findings.iter().enumerate() {
+            _write_table_row(index, finding, source_map, out)?;
+        }
+        writeln!(out, "</tbody>\n</table>")?;
+
+        for (index, finding) in report.findings.iter().enumerate() {
+            _write_finding(index, finding, source_map, out)?;
+        }
+
+        if !report.clusters.is_empty() {
+            writeln!(out, "<h2>Suggested WASM modules</h2>")?;
+            for cluster in &report.clusters {
+                _write_cluster(cluster, report.modules, source_map, out)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn _write_aggregates(aggregates: &[Aggregate], out: &mut dyn Write) -> io::Result<()> {
+        writeln!(out, "<table>\n<thead><tr>")?;
+        for header in ["Path", "Files", "Functions", "Flagged", "Mean score", "Max score"] {
+            writeln!(out, "<th>{}</th>", header)?;
+        }
+        writeln!(out, "</tr></thead>\n<tbody>")?;
+        for aggregate in aggregates {
+            writeln!(
+                out,
+                "<tr><td>{}</td><td class=\"number\" data-value=\"{1}\">{1}</td><td class=\"number\" data-value=\"{2}\">{2}</td>\
+                 <td class=\"number\" data-value=\"{3}\">{3}</td><td class=\"number\" data-value=\"{4}\">{4:.1}</td>\
+                 <td class=\"number\" data-value=\"{5}\"><span class=\"score {6}\">{5}/10</span></td></tr>",
+                _escape(&aggregate.path),
+                aggregate.files,
+                aggregate.functions,
+                aggregate.flagged,
+                aggregate.mean_score,
+                aggregate.max_score,
+                _band_class(aggregate.max_score),
+            )?;
+        }
+        writeln!(out, "</tbody>\n</table>")
+    }
+
+    fn _known_findings_note(report: &AnalysisReport) -> String {
+        match report.known_findings {
+            Some(known_findings) if known_findings > 0 => format!(", {} known findings from the baseline not shown", known_findings),
+            _ => String::new(),
+        }
+    }
+
+    fn _write_table_row(index: usize, finding: &Finding, source_map: &Rc<SourceMap>, out: &mut dyn Write) -> io::Result<()> {
+        let function = finding.function;
+        let numeric_share = function.type_summary.numeric_share().map(|numeric_share| numeric_share * 100.0);
+        writeln!(
+            out,
+            "<tr><td><a href=\"#finding-{}\">{}</a></td><td>{}</td><td class=\"number\" data-value=\"{}\"><span class=\"score {}\">{}/10</span></td>\
+             <td class=\"number\" data-value=\"{2}\">{2}</td>",
+            index,
+            _escape(function.display_name()),
+            _escape(&_location(source_map, function.span.lo)),
+            finding.score,
+            _band_class(finding.score),
+            finding.score,
+        )?;
+        writeln!(
+            out,
+            "<td class=\"number\" data-value=\"{0}\">{0}</td><td class=\"number\" data-value=\"{1}\">{1}</td>\
+             <td class=\"number\" data-value=\"{2}\">{2}</td><td class=\"number\" data-value=\"{3}\">{3}</td>\
+             <td class=\"number\" data-value=\"{4}\">{4}</td><td class=\"number\" data-value=\"{5:.0}\">{6}</td></tr>",
+            function.cyclomatic_complexity,
+            function.cognitive_complexity,
+            function.effective_loop_depth(),
+            function.arithmetic_operations,
+            function.string_operations,
+            // Functions without typed bindings sort below 0%
+            numeric_share.unwrap_or(-1.0),
+            numeric_share.map_or("n/a".to_string(), |numeric_share| format!("{:.0}%", numeric_share)),
+        )
+    }
+
+    fn _write_finding(index: usize, finding: &Finding, source_map: &Rc<SourceMap>, out: &mut dyn Write) -> io::Result<()> {
+        let function = finding.function;
+        writeln!(out, "<section class=\"finding\" id=\"finding-{}\">", index)?;
+        writeln!(
+            out,
+            "<h2>{} <span class=\"score {}\">{}/10</span></h2>\n<p>{}</p>\n<dl>",
+            _escape(function.display_name()),
+            _band_class(finding.score),
+            finding.score,
+            _escape(&_location(source_map, function.span.lo)),
+        )?;
+        if let Some(rank) = finding.rank {
+            writeln!(out, "<dt>Percentile</dt><dd>{:.0} (top {:.0}% of the project's functions)</dd>", rank.percentile, rank.top_share)?;
+        }
+        writeln!(out, "<dt>Metrics</dt><dd>{}</dd>", _escape(&format_metrics(function)))?;
+        match &function.recursion {
+            Recursion::None => {}
+            Recursion::Direct => writeln!(out, "<dt>Recursion</dt><dd>direct</dd>")?,
+            Recursion::Mutual(cycle) => writeln!(out, "<dt>Recursion</dt><dd>mutual ({})</dd>", _escape(&cycle.join(", ")))?,
+        }
+        writeln!(out, "<dt>Numeric data</dt><dd>{}</dd>", _escape(&format_type_summary(function)))?;
+        let usage = &function.typed_array_usage;
+        if usage.constructions + usage.loop_accesses + usage.image_data_reads + usage.data_view_accesses > 0 {
+            writeln!(
+                out,
+                "<dt>Typed arrays</dt><dd>{} constructed, {} indexed in loops, {} getImageData, {} DataView accesses{}</dd>",
+                usage.constructions,
+                usage.loop_accesses,
+                usage.image_data_reads,
+                usage.data_view_accesses,
+                if usage.is_zero_copy_candidate() { " [zero-copy candidate]" } else { "" }
+            )?;
+        }
+        writeln!(out, "</dl>")?;
+        writeln!(out, "<pre class=\"source\"><code>{}</code></pre>\n</section>", _highlight_source(function, source_map))
+    }
+
+    fn _write_cluster(cluster: &Cluster, modules: &[ModuleAnalysis], source_map: &Rc<SourceMap>, out: &mut dyn Write) -> io::Result<()> {
+        let entry_point = ModuleAnalysis::function(modules, cluster.entry_point);
+        writeln!(
+            out,
+            "<h3>{}</h3>\n<dl>\n<dt>Entry point</dt><dd>{}</dd>\n<dt>Combined score</dt><dd>{} ({} candidates)</dd>\n\
+             <dt>Candidates</dt><dd>{}</dd>\n<dt>Boundary API</dt><dd>{}</dd>\n<dt>Helpers to bring along</dt><dd>{}</dd>\n</dl>",
+            _escape(entry_point.display_name()),
+            _escape(&_location(source_map, entry_point.span.lo)),
+            cluster.combined_score,
+            cluster.candidates.len(),
+            _escape(&format_function_list(modules, &cluster.candidates)),
+            _escape(&format_function_list(modules, &cluster.boundary)),
+            _escape(&format_function_list(modules, &cluster.helpers)),
+        )
+    }
+
+    /// Source of the function from the start of its first line, with line numbers,
+    /// syntax highlighting and its loop and arithmetic hotspots marked.
+    fn _highlight_source(function: &FunctionAnalysis, source_map: &Rc<SourceMap>) -> String {
+        let location = source_map.lookup_char_pos(function.span.lo);
+        let file = &location.file;
+        let source_code: &str = &file.src;
+        let function_start = (function.span.lo - file.start_pos).0 as usize;
+        let end = ((function.span.hi - file.start_pos).0 as usize).min(source_code.len());
+
+        // Lexed like it was parsed, markup before the script on the same line is left out
+        let extension = match &file.name {
+            FileName::Real(path) => path.extension().and_then(OsStr::to_str).unwrap_or_default(),
+            _ => "",
+        };
+        let block = source_blocks(source_code, extension).into_iter()
+            .find(|block| block.range.contains(&function_start));
+        let (block_start, syntax) = block.map_or((0, Syntax::default()), |block| (block.range.start, block.syntax));
+        let start = source_code[..function_start].rfind('\n').map(|pos| pos + 1).unwrap_or(0).max(block_start);
+        let text = &source_code[start..end];
+        let base = file.start_pos + BytePos(start as u32);
+
+        // Classes of every byte of `text`, from tokens and hotspots
+        let mut token_classes: Vec<&str> = text.bytes()
+            .map(|byte| if byte.is_ascii_whitespace() { "" } else { "tok-comment" })
+            .collect();
+        let input = StringInput::new(text, base, base + BytePos(text.len() as u32));
+        for token in Lexer::new(syntax, EsVersion::latest(), input, None) {
+            let class = _token_class(&token.token);
+            let token_start = (token.span.lo - base).0 as usize;
+            let token_end = ((token.span.hi - base).0 as usize).min(text.len());
+            for byte_class in &mut token_classes[token_start..token_end] {
+                *byte_class = class;
+            }
+        }
+
+        let mut hotspot_classes: Vec<(bool, bool)> = vec![(false, false); text.len()];
+        for hotspot in &function.hotspots {
+            let hotspot_start = (hotspot.span.lo.0.saturating_sub(base.0) as usize).min(text.len());
+            let hotspot_end = (hotspot.span.hi.0.saturating_sub(base.0) as usize).min(text.len());
+            for flags in &mut hotspot_classes[hotspot_start..hotspot_end] {
+                match hotspot.kind {
+                    HotspotKind::Loop => flags.0 = true,
+                    HotspotKind::Arithmetic => flags.1 = true,
+                }
+            }
+        }
+
+        let mut html = String::new();
+        let mut line = location.line;
+        html.push_str(&format!("<span class=\"ln\">{}</span>", line));
+        let mut run = String::new();
+        let mut run_classes = String::new();
+        for (index, c) in text.char_indices() {
+            let classes = if c == '\n' {
+                String::new()
+            } else {
+                let (in_loop, in_arithmetic) = hotspot_classes[index];
+                [token_classes[index], if in_loop { "hot-loop" } else { "" }, if in_arithmetic { "hot-arithmetic" } else { "" }]
+                    .iter()
+                    .filter(|class| !class.is_empty())
+                    .copied()
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            };
+            if classes != run_classes || c == '\n' {
+                _push_run(&mut html, &run, &run_classes);
+                run.clear();
+                run_classes = classes;
+            }
+            if c == '\n' {
+                line += 1;
+                html.push_str(&format!("\n<span class=\"ln\">{}</span>", line));
+            } else {
+                run.push(c);
+            }
+        }
+        _push_run(&mut html, &run, &run_classes);
+        html
+    }
+
+    fn _push_run(html: &mut String, run: &str, classes: &str) {
+        if run.is_empty() {
+            return;
+        }
+        if classes.is_empty() {
+            html.push_str(&_escape(run));
+        } else {
+            html.push_str(&format!("<span class=\"{}\">{}</span>", classes, _escape(run)));
+        }
+    }
+
+    fn _token_class(token: &Token) -> &'static str {
+        match token {
+            Token::Word(Word::Keyword(_) | Word::Null | Word::True | Word::False) => "tok-keyword",
+            Token::Str { .. } | Token::Template { .. } | Token::Regex(..) | Token::BackQuote => "tok-string",
+            Token::Num { .. } | Token::BigInt { .. } => "tok-number",
+            _ => "",
+        }
+    }
+
+    fn _location(source_map: &Rc<SourceMap>, position: BytePos) -> String {
+        let location = source_map.lookup_char_pos(position);
+        format!("{}:{}:{}", location.file.name, location.line, location.col_display)
+    }
+
+    fn _band_class(score: usize) -> &'static str {
+        match score {
+            _ if score >= DANGER => "band-danger",
+            _ if score >= WARNING => "band-warning",
+            _ if score >= CONCERN => "band-concern",
+            _ => "band-low",
+        }
+    }
+
+    fn _escape(text: &str) -> String {
+        text.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;")
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::path::Path;
+        use crate::parsing::Project;
+        use super::_highlight_source;
+
+        fn highlighted(file_name: &str, source: &str) -> String {
+            let project = Project::from_source(Path::new(file_name), source.to_string());
+            let modules = project.modules();
+            _highlight_source(&modules[0].functions[0], &project.source_map)
+        }
+
+        #[test]
+        fn tsx_files_are_lexed_with_jsx() {
+            // As plain TypeScript the apostrophe would open a string
+            let html = highlighted("view.tsx", "function view(items: number[]) {\n  return <p>don't {items.length}</p>;\n}");
+            assert!(!html.contains("tok-string"), "{}", html);
+            assert!(html.contains("<span class=\"tok-keyword\">return</span>"), "{}", html);
+        }
+
+        #[test]
+        fn component_blocks_are_lexed_in_their_lang() {
+            let source = "<template><p>it's</p></template>\n<script lang=\"tsx\">function view(n: number) { return <b>n's</b>; }</script>";
+            let html = highlighted("View.vue", source);
+            assert!(!html.contains("tok-string"), "{}", html);
+            // Markup before the script on the same line is not shown
+            assert!(!html.contains("script"), "{}", html);
+            assert!(html.starts_with("<span class=\"ln\">2</span><span class=\"tok-keyword\">function</span>"), "{}", html);
+        }
+    }
+}
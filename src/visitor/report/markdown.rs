@@ -0,0 +1,152 @@
+pub mod markdown {
+    use std::io::{self, Write};
+    use std::rc::Rc;
+    use swc_common::SourceMap;
+    use crate::visitor::top_reason;
+    use crate::visitor::report::findings::findings::AnalysisReport;
+    use crate::visitor::report::report::report::{format_aggregate, CONCERN, DANGER, WARNING};
+
+    /// Totals per severity band and a compact table of the findings, meant for pull request comments.
+    pub fn write_markdown_report(report: &AnalysisReport, source_map: &Rc<SourceMap>, out: &mut dyn Write) -> io::Result<()> {
+        writeln!(out, "### wasm-grate\n\n{}\n", format_aggregate(&report.summary.total, true))?;
+
+        let in_band = |low: usize, high: usize| report.findings.iter()
+            .filter(|finding| (low..high).contains(&finding.score))
+            .count();
+        writeln!(out, "| Band | Score | Findings |\n| --- | --- | ---: |")?;
+        writeln!(out, "| DANGER | {}-10 | {} |", DANGER, in_band(DANGER, usize::MAX))?;
+        writeln!(out, "| WARNING | {}-{} | {} |", WARNING, DANGER - 1, in_band(WARNING, DANGER))?;
+        writeln!(out, "| CONCERN | {}-{} | {} |", CONCERN, WARNING - 1, in_band(CONCERN, WARNING))?;
+        if in_band(0, CONCERN) > 0 {
+            writeln!(out, "| below CONCERN | 0-{} | {} |", CONCERN - 1, in_band(0, CONCERN))?;
+        }
+        writeln!(out, "| **Total** | | **{}** |\n", report.findings.len())?;
+
+        if let Some(known_findings) = report.known_findings.filter(|&known_findings| known_findings > 0) {
+            writeln!(out, "{} known findings from the baseline are not listed.\n", known_findings)?;
+        }
+
+        if report.summary_only {
+            writeln!(out, "| Directory | Files | Functions | Flagged | Mean score | Max score |")?;
+            writeln!(out, "| --- | ---: | ---: | ---: | ---: | ---: |")?;
+            for aggregate in &report.summary.directories {
+                writeln!(
+                    out,
+                    "| {} | {} | {} | {} | {:.1} | {}/10 |",
+                    _escape_cell(&aggregate.path),
+                    aggregate.files,
+                    aggregate.functions,
+                    aggregate.flagged,
+                    aggregate.mean_score,
+                    aggregate.max_score
+                )?;
+            }
+            return Ok(());
+        }
+
+        if report.findings.is_empty() {
+            return writeln!(out, "No WASM candidates found.");
+        }
+
+        writeln!(out, "| Function | Location | Score | Top reason |\n| --- | --- | ---: | --- |")?;
+        for finding in &report.findings {
+            let location = source_map.lookup_char_pos(finding.function.span.lo);
+            writeln!(
+                out,
+                "| `{}` | {}:{} | {}/10 | {} |",
+                _escape_cell(finding.function.display_name()),
+                _escape_cell(&location.file.name.to_string()),
+                location.line,
+                finding.score,
+                _escape_cell(&top_reason(finding.function, report.thresholds))
+            )?;
+        }
+        Ok(())
+    }
+
+    // Pipes end table cells even inside code spans
+    fn _escape_cell(text: &str) -> String {
+        text.replace('|', "\\|").replace('`', "'")
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::path::Path;
+        use crate::fixtures::fixtures::ZERO_THRESHOLDS;
+        use crate::parsing::Project;
+        use crate::visitor::function_analysis::function_analysis::FunctionId;
+        use crate::visitor::report::findings::findings::{AnalysisReport, Finding};
+        use crate::visitor::report::summary::summary::Summary;
+        use super::{_escape_cell, write_markdown_report};
+
+        /// Markdown report of `scores`, every finding being the single function of `file_name`.
+        fn markdown(file_name: &str, scores: &[usize]) -> String {
+            let project = Project::from_source(Path::new(file_name), "function mix(a) { for (;;) { a *= 2; } }".to_string());
+            let modules = project.modules();
+            let id = FunctionId { module: 0, function: 0 };
+            let findings = scores.iter()
+                .map(|&score| Finding { id, function: &modules[0].functions[0], score, raw_score: 0.0, rank: None })
+                .collect();
+            let report = AnalysisReport {
+                modules: &modules,
+                thresholds: &ZERO_THRESHOLDS,
+                findings,
+                functions: None,
+                clusters: Vec::new(),
+                unused_suppressions: Vec::new(),
+                known_findings: None,
+                summary: Summary::default(),
+                summary_only: false,
+            };
+            let mut out = Vec::new();
+            write_markdown_report(&report, &project.source_map, &mut out).expect("Failed to write report");
+            String::from_utf8(out).unwrap()
+        }
+
+        fn band_rows(markdown: &str) -> Vec<&str> {
+            markdown.lines()
+                .skip_while(|line| !line.starts_with("| Band "))
+                .skip(2)
+                .take_while(|line| line.starts_with('|'))
+                .collect()
+        }
+
+        #[test]
+        fn findings_are_counted_in_the_band_of_their_score() {
+            let report = markdown("mix.js", &[0, 2, 3, 4, 5, 6, 7, 10]);
+            assert_eq!(band_rows(&report), vec![
+                "| DANGER | 7-10 | 2 |",
+                "| WARNING | 5-6 | 2 |",
+                "| CONCERN | 3-4 | 2 |",
+                "| below CONCERN | 0-2 | 2 |",
+                "| **Total** | | **8** |",
+            ]);
+        }
+
+        #[test]
+        fn empty_bands_are_listed_except_below_concern() {
+            let report = markdown("mix.js", &[7]);
+            assert_eq!(band_rows(&report), vec![
+                "| DANGER | 7-10 | 1 |",
+                "| WARNING | 5-6 | 0 |",
+                "| CONCERN | 3-4 | 0 |",
+                "| **Total** | | **1** |",
+            ]);
+            assert!(markdown("mix.js", &[]).contains("No WASM candidates found."));
+        }
+
+        #[test]
+        fn pipes_and_backticks_do_not_break_table_cells() {
+            assert_eq!(_escape_cell("Blur.apply"), "Blur.apply");
+            assert_eq!(_escape_cell("mix|blend"), "mix\\|blend");
+            assert_eq!(_escape_cell("a || b"), "a \\|\\| b");
+            assert_eq!(_escape_cell("`code`"), "'code'");
+
+            let report = markdown("src/a|b.js", &[8]);
+            let row = report.lines().find(|line| line.starts_with("| `mix`")).expect("No finding row");
+            assert!(row.starts_with("| `mix` | src/a\\|b.js:1 | 8/10 | "), "{}", row);
+            // Unescaped pipes only delimit the five cells
+            assert_eq!(row.replace("\\|", "").matches('|').count(), 5, "{}", row);
+        }
+    }
+}
@@ -1,2 +1,14 @@
+#[allow(clippy::module_inception)]
 pub mod report;
-pub use report::report::report_function;
+#[allow(clippy::module_inception)]
+pub mod findings;
+pub use findings::findings::{write_report, AnalysisReport, Finding, ReportFormat};
+#[allow(clippy::module_inception)]
+pub mod html;
+#[allow(clippy::module_inception)]
+pub mod markdown;
+#[allow(clippy::module_inception)]
+pub mod records;
+#[allow(clippy::module_inception)]
+pub mod summary;
+pub use summary::summary::{Aggregate, Summary};
@@ -0,0 +1,279 @@
+pub mod records {
+    use std::collections::HashSet;
+    use std::io::{self, Write};
+    use std::rc::Rc;
+    use serde::Serialize;
+    use serde_json::Value;
+    use swc_common::SourceMap;
+    use crate::visitor::function_analysis::function_analysis::{FunctionId, Recursion};
+    use crate::visitor::report::findings::findings::{AnalysisReport, Finding};
+    use crate::visitor::report::summary::summary::Aggregate;
+
+    /// Columns of the CSV report, in the order of the fields of `FunctionRecord`.
+    const COLUMNS: [&str; 30] = [
+        "file", "line", "column", "function", "flagged", "score", "raw_score", "percentile",
+        "cyclomatic_complexity", "cognitive_complexity", "loop_depth", "max_loop_depth", "caller_loop_depth",
+        "arithmetic_operations", "string_operations", "call_depth", "recursion",
+        "numeric_share", "int", "float", "typed_array", "string", "object", "unknown",
+        "typed_array_constructions", "typed_array_loop_accesses", "image_data_reads", "data_view_accesses",
+        "zero_copy_candidate", "body_hash",
+    ];
+
+    /// Columns of the CSV report with `--summary`.
+    const SUMMARY_COLUMNS: [&str; 7] = ["kind", "path", "files", "functions", "flagged", "mean_score", "max_score"];
+
+    /// One line of the summary: the whole analysis, a directory or a file.
+    #[derive(Serialize)]
+    struct AggregateRecord<'a> {
+        kind: &'static str,
+        #[serde(flatten)]
+        aggregate: &'a Aggregate,
+    }
+
+    /// Every raw metric of one function, a CSV row or an NDJSON line.
+    #[derive(Serialize)]
+    struct FunctionRecord<'a> {
+        file: String,
+        line: usize,
+        column: usize,
+        function: &'a str,
+        /// Reported as a finding
+        flagged: bool,
+        score: usize,
+        raw_score: f64,
+        percentile: Option<f64>,
+        cyclomatic_complexity: usize,
+        cognitive_complexity: usize,
+        loop_depth: usize,
+        max_loop_depth: usize,
+        caller_loop_depth: usize,
+        arithmetic_operations: usize,
+        string_operations: usize,
+        call_depth: usize,
+        recursion: &'static str,
+        numeric_share: Option<f64>,
+        int: usize,
+        float: usize,
+        typed_array: usize,
+        string: usize,
+        object: usize,
+        unknown: usize,
+        typed_array_constructions: usize,
+        typed_array_loop_accesses: usize,
+        image_data_reads: usize,
+        data_view_accesses: usize,
+        zero_copy_candidate: bool,
+        body_hash: String,
+    }
+
+    pub fn write_csv_report(report: &AnalysisReport, source_map: &Rc<SourceMap>, out: &mut dyn Write) -> io::Result<()> {
+        if report.summary_only {
+            writeln!(out, "{}", SUMMARY_COLUMNS.join(","))?;
+            for record in _aggregate_records(report) {
+                _write_csv_row(&SUMMARY_COLUMNS, &record, out)?;
+            }
+            return Ok(());
+        }
+
+        writeln!(out, "{}", COLUMNS.join(","))?;
+        for record in _records(report, source_map) {
+            _write_csv_row(&COLUMNS, &record, out)?;
+        }
+        Ok(())
+    }
+
+    pub fn write_ndjson_report(report: &AnalysisReport, source_map: &Rc<SourceMap>, out: &mut dyn Write) -> io::Result<()> {
+        if report.summary_only {
+            for record in _aggregate_records(report) {
+                _write_json_line(&record, out)?;
+            }
+            return Ok(());
+        }
+
+        for record in _records(report, source_map) {
+            _write_json_line(&record, out)?;
+        }
+        Ok(())
+    }
+
+    fn _write_csv_row(columns: &[&str], record: &impl Serialize, out: &mut dyn Write) -> io::Result<()> {
+        let record = serde_json::to_value(record)?;
+        let row: Vec<String> = columns.iter()
+            .map(|column| match &record[*column] {
+                Value::Null => String::new(),
+                Value::String(text) => _escape_csv(text),
+                value => value.to_string(),
+            })
+            .collect();
+        writeln!(out, "{}", row.join(","))
+    }
+
+    fn _write_json_line(record: &impl Serialize, out: &mut dyn Write) -> io::Result<()> {
+        serde_json::to_writer(&mut *out, record)?;
+        writeln!(out)
+    }
+
+    fn _aggregate_records<'a>(report: &'a AnalysisReport) -> impl Iterator<Item = AggregateRecord<'a>> {
+        let summary = &report.summary;
+        std::iter::once(AggregateRecord { kind: "total", aggregate: &summary.total })
+            .chain(summary.directories.iter().map(|aggregate| AggregateRecord { kind: "directory", aggregate }))
+            .chain(summary.files.iter().map(|aggregate| AggregateRecord { kind: "file", aggregate }))
+    }
+
+    /// Every analyzed function with `--all`, else only the findings.
+    fn _records<'a>(report: &'a AnalysisReport, source_map: &'a Rc<SourceMap>) -> impl Iterator<Item = FunctionRecord<'a>> {
+        let flagged: HashSet<FunctionId> = report.findings.iter().map(|finding| finding.id).collect();
+        report.functions.as_deref().unwrap_or(&report.findings).iter()
+            .map(move |finding| _record(finding, flagged.contains(&finding.id), source_map))
+    }
+
+    fn _record<'a>(finding: &Finding<'a>, flagged: bool, source_map: &Rc<SourceMap>) -> FunctionRecord<'a> {
+        let function = finding.function;
+        let location = source_map.lookup_char_pos(function.span.lo);
+        let types = &function.type_summary;
+        let usage = &function.typed_array_usage;
+
+        FunctionRecord {
+            file: location.file.name.to_string(),
+            line: location.line,
+            column: location.col_display,
+            function: &function.qualified_name,
+            flagged,
+            score: finding.score,
+            raw_score: _round(finding.raw_score),
+            percentile: finding.rank.map(|rank| _round(rank.percentile)),
+            cyclomatic_complexity: function.cyclomatic_complexity,
+            cognitive_complexity: function.cognitive_complexity,
+            loop_depth: function.effective_loop_depth(),
+            max_loop_depth: function.max_loop_depth,
+            caller_loop_depth: function.caller_loop_depth,
+            arithmetic_operations: function.arithmetic_operations,
+            string_operations: function.string_operations,
+            call_depth: function.call_depth,
+            recursion: match function.recursion {
+                Recursion::None => "none",
+                Recursion::Direct => "direct",
+                Recursion::Mutual(_) => "mutual",
+            },
+            numeric_share: types.numeric_share().map(_round),
+            int: types.int,
+            float: types.float,
+            typed_array: types.typed_array,
+            string: types.string,
+            object: types.object,
+            unknown: types.unknown,
+            typed_array_constructions: usage.constructions,
+            typed_array_loop_accesses: usage.loop_accesses,
+            image_data_reads: usage.image_data_reads,
+            data_view_accesses: usage.data_view_accesses,
+            zero_copy_candidate: usage.is_zero_copy_candidate(),
+            body_hash: format!("{:016x}", function.body_hash),
+        }
+    }
+
+    // Widening f32 to f64 as is would write digits the metrics never had
+    fn _round(value: f32) -> f64 {
+        (value as f64 * 1000.0).round() / 1000.0
+    }
+
+    fn _escape_csv(text: &str) -> String {
+        if text.contains([',', '"', '\n', '\r']) {
+            format!("\"{}\"", text.replace('"', "\"\""))
+        } else {
+            text.to_string()
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::path::Path;
+        use serde_json::{json, Value};
+        use crate::Metrics;
+        use crate::fixtures::fixtures::ZERO_THRESHOLDS;
+        use crate::parsing::{process_source, AnalysisOptions};
+        use crate::visitor::report::ReportFormat;
+        use super::{_escape_csv, _write_csv_row, COLUMNS, SUMMARY_COLUMNS};
+
+        const SOURCE: &str = "function hot() { for (;;) { for (;;) {} } }\nfunction cold() {}";
+
+        fn report(file_name: &str, options: AnalysisOptions) -> String {
+            let mut out = Vec::new();
+            // Only `hot` goes over the thresholds
+            let thresholds = Metrics { cyclomatic_complexity: 1, ..ZERO_THRESHOLDS };
+            process_source(Path::new(file_name), SOURCE.to_string(), &thresholds, &options, &mut out);
+            String::from_utf8(out).expect("Report is not UTF-8")
+        }
+
+        #[test]
+        fn csv_fields_are_quoted_only_when_needed() {
+            for (text, expected) in [
+                ("mix", "mix"),
+                ("Blur.apply", "Blur.apply"),
+                ("a,b", "\"a,b\""),
+                ("say \"hi\"", "\"say \"\"hi\"\"\""),
+                ("two\nlines", "\"two\nlines\""),
+                ("carriage\rreturn", "\"carriage\rreturn\""),
+            ] {
+                assert_eq!(_escape_csv(text), expected, "case {:?}", text);
+            }
+        }
+
+        #[test]
+        fn csv_rows_escape_names_and_leave_missing_values_empty() {
+            let mut out = Vec::new();
+            let record = json!({ "function": "odd, \"name\"\nhere", "line": 3, "percentile": null });
+            _write_csv_row(&["function", "line", "percentile"], &record, &mut out).expect("Failed to write row");
+            assert_eq!(String::from_utf8(out).unwrap(), "\"odd, \"\"name\"\"\nhere\",3,\n");
+        }
+
+        #[test]
+        fn csv_report_has_one_column_per_header() {
+            let csv = report("src/odd, \"name\".js", AnalysisOptions { format: ReportFormat::Csv, ..AnalysisOptions::default() });
+            let lines: Vec<&str> = csv.lines().collect();
+            assert_eq!(lines[0], COLUMNS.join(","));
+            assert_eq!(lines.len(), 2, "{}", csv);
+            assert!(lines[1].starts_with("\"src/odd, \"\"name\"\".js\",1,0,hot,true,"), "{}", lines[1]);
+            // Dropping the quoted field leaves the unquoted columns
+            let unquoted = lines[1].rsplit_once(".js\",").unwrap().1;
+            assert_eq!(unquoted.split(',').count(), COLUMNS.len() - 1);
+        }
+
+        #[test]
+        fn ndjson_lines_are_objects_with_every_column() {
+            let options = AnalysisOptions { format: ReportFormat::Ndjson, all_functions: true, ..AnalysisOptions::default() };
+            let ndjson = report("lib/mix.js", options);
+            let records: Vec<Value> = ndjson.lines()
+                .map(|line| serde_json::from_str(line).expect("Line is not JSON"))
+                .collect();
+            assert_eq!(records.len(), 2);
+            for record in &records {
+                let mut keys: Vec<&str> = record.as_object().expect("Line is not an object").keys().map(String::as_str).collect();
+                let mut columns = COLUMNS.to_vec();
+                keys.sort();
+                columns.sort();
+                assert_eq!(keys, columns);
+            }
+            assert_eq!((&records[0]["function"], &records[0]["flagged"]), (&json!("hot"), &json!(true)));
+            assert_eq!((&records[1]["function"], &records[1]["flagged"]), (&json!("cold"), &json!(false)));
+            assert_eq!(records[0]["file"], json!("lib/mix.js"));
+            assert_eq!(records[0]["body_hash"].as_str().map(str::len), Some(16));
+            assert!(records[0]["percentile"].is_null());
+        }
+
+        #[test]
+        fn ndjson_summary_lists_total_then_directories_then_files() {
+            let options = AnalysisOptions { format: ReportFormat::Ndjson, summary_only: true, ..AnalysisOptions::default() };
+            let ndjson = report("lib/mix.js", options);
+            let records: Vec<Value> = ndjson.lines().map(|line| serde_json::from_str(line).expect("Line is not JSON")).collect();
+            let kinds: Vec<&str> = records.iter().map(|record| record["kind"].as_str().unwrap()).collect();
+            assert_eq!(kinds.first(), Some(&"total"));
+            assert_eq!(kinds.last(), Some(&"file"));
+            for record in &records {
+                for column in SUMMARY_COLUMNS {
+                    assert!(record.get(column).is_some(), "{} missing in {}", column, record);
+                }
+            }
+            assert_eq!((&records[0]["functions"], &records[0]["flagged"]), (&json!(2), &json!(1)));
+        }
+    }
+}
@@ -1,17 +1,72 @@
 pub mod report {
+    use std::io::{self, Write};
     use std::rc::Rc;
     use colored::{Color, Colorize};
-    use swc_common::{SourceMap, Span};
+    use swc_common::SourceMap;
+    use crate::visitor::{FunctionAnalysis, ModuleAnalysis, ProjectRank};
+    use crate::visitor::report::findings::findings::{AnalysisReport, Finding};
+    use crate::visitor::clustering::clustering::Cluster;
+    use crate::visitor::function_analysis::function_analysis::{FunctionId, Recursion};
+    use crate::visitor::report::summary::summary::{Aggregate, Summary};
+    use crate::visitor::suppressions::suppressions::Suppression;
 
-    const CONCERN: usize = 3;
-    const WARNING: usize = 5;
-    const DANGER: usize = 7;
+    pub const CONCERN: usize = 3;
+    pub const WARNING: usize = 5;
+    pub const DANGER: usize = 7;
 
-    pub fn report_function(
-        span: Span,
-        source_map: &Rc<SourceMap>,
-        score: usize,
-    ) {
+    /// The report printed to the terminal.
+    pub fn write_text_report(report: &AnalysisReport, source_map: &Rc<SourceMap>, out: &mut dyn Write) -> io::Result<()> {
+        if !report.summary_only {
+            for finding in &report.findings {
+                _write_function(finding, source_map, out)?;
+            }
+            for cluster in &report.clusters {
+                _write_cluster(cluster, report.modules, source_map, out)?;
+            }
+        }
+        for suppression in &report.unused_suppressions {
+            _write_unused_suppression(suppression, source_map, out)?;
+        }
+        if let Some(known_findings) = report.known_findings.filter(|&known_findings| known_findings > 0) {
+            writeln!(out, "{}: {} known findings not reported", "Baseline".blue(), known_findings)?;
+        }
+        _write_summary(&report.summary, report.summary_only, out)
+    }
+
+    /// Totals of the analysis, and with `--summary` the aggregates of every directory and file.
+    fn _write_summary(summary: &Summary, detailed: bool, out: &mut dyn Write) -> io::Result<()> {
+        if detailed {
+            for (label, aggregates) in [("Directory", &summary.directories), ("File", &summary.files)] {
+                for aggregate in aggregates.iter() {
+                    writeln!(out, "{} {}: {}", label.blue(), aggregate.path, format_aggregate(aggregate, label != "File"))?;
+                }
+                if !aggregates.is_empty() {
+                    writeln!(out)?;
+                }
+            }
+        }
+        writeln!(out, "{}: {}", "Summary".green(), format_aggregate(&summary.total, true))
+    }
+
+    pub fn format_aggregate(aggregate: &Aggregate, with_files: bool) -> String {
+        let files = match aggregate.files {
+            _ if !with_files => String::new(),
+            1 => " in 1 file".to_string(),
+            files => format!(" in {} files", files),
+        };
+        format!(
+            "{} of {} functions flagged{}, mean score {:.1}, max score {}",
+            aggregate.flagged,
+            aggregate.functions,
+            files,
+            aggregate.mean_score,
+            aggregate.max_score
+        )
+    }
+
+    fn _write_function(finding: &Finding, source_map: &Rc<SourceMap>, out: &mut dyn Write) -> io::Result<()> {
+        let function = finding.function;
+        let span = function.span;
         let start_location = source_map.lookup_char_pos(span.lo());
 
         let file_name = &start_location.file.name.to_string();
@@ -21,17 +76,73 @@ pub mod report {
         let start_index = source_map.lookup_byte_offset(span.lo()).pos.0 as usize;
 
         // Extract the function declaration snippet
-        let snippet = _extract_function_declaration(&source_code, start_index);
+        let snippet = _extract_function_declaration(source_code, start_index);
 
-        println!(
-            "{}:{}:{}\n{}\n{}: {}\n",
+        writeln!(
+            out,
+            "{}:{}:{}\n{}\n{}{}: {}\n{}{}: {}\n{}{}: {}\n",
             file_name,
             start_location.line,
             start_location.col_display,
-            _get_colorized_score(score),
+            _get_colorized_score(finding.score),
+            _format_rank(finding.rank),
+            "Metrics".blue(),
+            format_metrics(function),
+            _format_recursion(function),
+            "Numeric data".blue(),
+            format_type_summary(function),
+            _format_typed_array_usage(function),
             "Declaration".blue(),
             snippet.trim()
-        );
+        )
+    }
+
+    fn _write_cluster(cluster: &Cluster, modules: &[ModuleAnalysis], source_map: &Rc<SourceMap>, out: &mut dyn Write) -> io::Result<()> {
+        let entry_point = ModuleAnalysis::function(modules, cluster.entry_point);
+        let start_location = source_map.lookup_char_pos(entry_point.span.lo());
+
+        writeln!(
+            out,
+            "{}: {}\n{}: {}:{}:{}\n{}: {} ({} candidates)\n{}: {}\n{}: {}\n{}: {}\n",
+            "Suggested WASM module".green(),
+            entry_point.display_name(),
+            "Entry point".blue(),
+            start_location.file.name,
+            start_location.line,
+            start_location.col_display,
+            "Combined score".blue(),
+            cluster.combined_score,
+            cluster.candidates.len(),
+            "Candidates".blue(),
+            format_function_list(modules, &cluster.candidates),
+            "Boundary API".blue(),
+            format_function_list(modules, &cluster.boundary),
+            "Helpers to bring along".blue(),
+            format_function_list(modules, &cluster.helpers)
+        )
+    }
+
+    fn _write_unused_suppression(suppression: &Suppression, source_map: &Rc<SourceMap>, out: &mut dyn Write) -> io::Result<()> {
+        let start_location = source_map.lookup_char_pos(suppression.span.lo());
+        writeln!(
+            out,
+            "{}:{}:{}\n{}: {} does not suppress any finding\n",
+            start_location.file.name,
+            start_location.line,
+            start_location.col_display,
+            "Unused suppression".yellow(),
+            suppression.kind.directive()
+        )
+    }
+
+    pub fn format_function_list(modules: &[ModuleAnalysis], functions: &[FunctionId]) -> String {
+        if functions.is_empty() {
+            return "none".to_string();
+        }
+        functions.iter()
+            .map(|&id| ModuleAnalysis::function(modules, id).display_name())
+            .collect::<Vec<_>>()
+            .join(", ")
     }
 
        fn _extract_function_declaration(source_code: &str, start_index: usize) -> String {
@@ -57,6 +168,79 @@ pub mod report {
         signature.to_string()
     }
 
+    pub fn format_metrics(function: &FunctionAnalysis) -> String {
+        let loop_depth = if function.caller_loop_depth > 0 {
+            format!("{} ({} inside, {} at call sites)", function.effective_loop_depth(), function.max_loop_depth, function.caller_loop_depth)
+        } else {
+            function.max_loop_depth.to_string()
+        };
+        format!(
+            "cyclomatic {}, cognitive {}, loop depth {}, arithmetic {}, string {}, call depth {}",
+            function.cyclomatic_complexity,
+            function.cognitive_complexity,
+            loop_depth,
+            function.arithmetic_operations,
+            function.string_operations,
+            function.call_depth
+        )
+    }
+
+    fn _format_rank(rank: Option<ProjectRank>) -> String {
+        match rank {
+            Some(rank) => format!(
+                "{}: {:.0} (top {:.0}% of the project's functions)\n",
+                "Percentile".blue(),
+                rank.percentile,
+                rank.top_share
+            ),
+            None => String::new(),
+        }
+    }
+
+    fn _format_recursion(function: &FunctionAnalysis) -> String {
+        match &function.recursion {
+            Recursion::None => String::new(),
+            Recursion::Direct => format!("{}: direct\n", "Recursion".blue()),
+            Recursion::Mutual(cycle) => format!("{}: mutual ({})\n", "Recursion".blue(), cycle.join(", ")),
+        }
+    }
+
+    pub fn format_type_summary(function: &FunctionAnalysis) -> String {
+        let summary = &function.type_summary;
+        format!(
+            "{} (int {}, float {}, typed array {}, string {}, object {}, unknown {})",
+            summary.numeric_share().map_or("n/a".to_string(), |numeric_share| format!("{:.0}%", numeric_share * 100.0)),
+            summary.int,
+            summary.float,
+            summary.typed_array,
+            summary.string,
+            summary.object,
+            summary.unknown
+        )
+    }
+
+    fn _format_typed_array_usage(function: &FunctionAnalysis) -> String {
+        let usage = &function.typed_array_usage;
+        if usage.constructions + usage.loop_accesses + usage.image_data_reads + usage.data_view_accesses == 0 {
+            return String::new();
+        }
+
+        let tag = if usage.is_zero_copy_candidate() {
+            format!(" {}", "[zero-copy candidate]".green())
+        } else {
+            String::new()
+        };
+        format!(
+            "{}: {} constructed, {} indexed in loops, {} getImageData, {} DataView accesses{}\n",
+            "Typed arrays".blue(),
+            usage.constructions,
+            usage.loop_accesses,
+            usage.image_data_reads,
+            usage.data_view_accesses,
+            tag
+        )
+    }
+
     fn _get_colorized_score(score: usize) -> String {
         let score_label = format!("Complexity: {}/10", score);
 
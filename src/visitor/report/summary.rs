@@ -0,0 +1,216 @@
+pub mod summary {
+    use std::collections::{BTreeMap, HashSet};
+    use std::path::Path;
+    use serde::Serialize;
+    use crate::visitor::ModuleAnalysis;
+    use crate::visitor::function_analysis::function_analysis::FunctionId;
+
+    /// Scores of the functions of a file or directory.
+    #[derive(Clone, Debug, Default, Serialize)]
+    pub struct Aggregate {
+        /// Relative to the analyzed path, `.` for the path itself
+        pub path: String,
+        pub files: usize,
+        pub functions: usize,
+        /// Functions reported as findings
+        pub flagged: usize,
+        pub mean_score: f64,
+        pub max_score: usize,
+        #[serde(skip)]
+        total_score: usize,
+    }
+
+    impl Aggregate {
+        fn new(path: String) -> Self {
+            Aggregate { path, ..Aggregate::default() }
+        }
+
+        /// Sum of the scores of all functions.
+        pub fn total_score(&self) -> usize {
+            self.total_score
+        }
+
+        fn add(&mut self, score: usize, flagged: bool) {
+            self.functions += 1;
+            self.flagged += usize::from(flagged);
+            self.total_score += score;
+            self.max_score = self.max_score.max(score);
+            self.mean_score = self.total_score as f64 / self.functions as f64;
+        }
+    }
+
+    /// Aggregates over the whole analysis, each directory and each file,
+    /// directories and files sorted from the most flagged functions.
+    #[derive(Debug, Default)]
+    pub struct Summary {
+        pub total: Aggregate,
+        pub directories: Vec<Aggregate>,
+        pub files: Vec<Aggregate>,
+    }
+
+    impl Summary {
+        /// `scores` holds every analyzed function, `flagged` the reported ones.
+        pub fn collect(modules: &[ModuleAnalysis], scores: &[(FunctionId, usize)], flagged: &[FunctionId], root: &Path) -> Self {
+            let mut total = Aggregate::new(".".to_string());
+            let mut directories: BTreeMap<String, Aggregate> = BTreeMap::new();
+            let mut files: Vec<Aggregate> = Vec::with_capacity(modules.len());
+
+            let flagged: HashSet<FunctionId> = flagged.iter().copied().collect();
+            let mut scores_by_module: Vec<Vec<(FunctionId, usize)>> = vec![Vec::new(); modules.len()];
+            for &(id, score) in scores {
+                scores_by_module[id.module].push((id, score));
+            }
+
+            for (module, module_scores) in modules.iter().zip(&scores_by_module) {
+                let relative = module.path.strip_prefix(root).ok()
+                    .filter(|relative| !relative.as_os_str().is_empty())
+                    .unwrap_or(&module.path);
+                let mut file = Aggregate::new(_display_path(relative));
+                file.files = 1;
+                for &(id, score) in module_scores {
+                    file.add(score, flagged.contains(&id));
+                }
+
+                // Every directory counts the files below it, not only the direct children
+                for directory in relative.ancestors().skip(1).filter(|directory| !directory.as_os_str().is_empty()) {
+                    let directory = _display_path(directory);
+                    let aggregate = directories.entry(directory.clone()).or_insert_with(|| Aggregate::new(directory));
+                    _merge(aggregate, &file);
+                }
+                _merge(&mut total, &file);
+                files.push(file);
+            }
+
+            let mut directories: Vec<Aggregate> = directories.into_values().collect();
+            _sort(&mut directories);
+            _sort(&mut files);
+            Summary { total, directories, files }
+        }
+    }
+
+    fn _merge(aggregate: &mut Aggregate, other: &Aggregate) {
+        aggregate.files += other.files;
+        aggregate.functions += other.functions;
+        aggregate.flagged += other.flagged;
+        aggregate.total_score += other.total_score;
+        aggregate.max_score = aggregate.max_score.max(other.max_score);
+        if aggregate.functions > 0 {
+            aggregate.mean_score = aggregate.total_score as f64 / aggregate.functions as f64;
+        }
+    }
+
+    fn _sort(aggregates: &mut [Aggregate]) {
+        aggregates.sort_by(|a, b| {
+            b.flagged.cmp(&a.flagged)
+                .then(b.max_score.cmp(&a.max_score))
+                .then(b.mean_score.total_cmp(&a.mean_score))
+                .then(a.path.cmp(&b.path))
+        });
+    }
+
+    fn _display_path(path: &Path) -> String {
+        path.to_string_lossy().replace('\\', "/")
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::fs;
+        use crate::fixtures::fixtures::project;
+        use crate::parsing::{AnalysisOptions, Project};
+        use crate::visitor::function_analysis::function_analysis::FunctionId;
+        use super::{Aggregate, Summary};
+
+        /// Files, functions, flagged and max score of an aggregate.
+        fn counts(aggregate: &Aggregate) -> (usize, usize, usize, usize) {
+            (aggregate.files, aggregate.functions, aggregate.flagged, aggregate.max_score)
+        }
+
+        fn find<'s>(aggregates: &'s [Aggregate], path: &str) -> &'s Aggregate {
+            aggregates.iter().find(|aggregate| aggregate.path == path).unwrap_or_else(|| panic!("No aggregate for {}", path))
+        }
+
+        /// Summary of the project where the function named `f<score>` scores `score`, flagged from 5.
+        fn summarize(files: &[(&str, &str)]) -> (Summary, std::path::PathBuf) {
+            let root = project("summary", files);
+            let analyzed = Project::load(&root, &AnalysisOptions::default());
+            let modules = analyzed.modules();
+            let mut scores = Vec::new();
+            let mut flagged = Vec::new();
+            for (module_index, module) in modules.iter().enumerate() {
+                for (function_index, function) in module.functions.iter().enumerate() {
+                    let id = FunctionId { module: module_index, function: function_index };
+                    let score: usize = function.display_name()[1..].parse().expect("Functions are named f<score>");
+                    scores.push((id, score));
+                    if score >= 5 {
+                        flagged.push(id);
+                    }
+                }
+            }
+            (Summary::collect(&modules, &scores, &flagged, &root), root)
+        }
+
+        #[test]
+        fn directories_count_every_file_below_them() {
+            let (summary, root) = summarize(&[
+                ("main.js", "function f1() {}"),
+                ("lib/math.js", "function f2() {}\nfunction f6() {}"),
+                ("lib/util/a.js", "function f9() {}"),
+                ("lib/util/b.js", "function f4() {}"),
+                ("docs/empty.js", ""),
+            ]);
+
+            assert_eq!(counts(&summary.total), (5, 5, 2, 9));
+            assert_eq!(summary.total.path, ".");
+            assert_eq!(summary.total.total_score(), 22);
+            assert_eq!(summary.total.mean_score, 4.4);
+
+            assert_eq!(counts(find(&summary.directories, "lib")), (3, 4, 2, 9));
+            assert_eq!(counts(find(&summary.directories, "lib/util")), (2, 2, 1, 9));
+            assert_eq!(find(&summary.directories, "lib/util").mean_score, 6.5);
+            // Files without functions still count, with no score
+            assert_eq!(counts(find(&summary.directories, "docs")), (1, 0, 0, 0));
+            assert_eq!(find(&summary.directories, "docs").mean_score, 0.0);
+            let _ = fs::remove_dir_all(root);
+        }
+
+        #[test]
+        fn root_files_are_in_the_total_only() {
+            let (summary, root) = summarize(&[("main.js", "function f3() {}"), ("lib/math.js", "function f5() {}")]);
+            let directories: Vec<&str> = summary.directories.iter().map(|aggregate| aggregate.path.as_str()).collect();
+            assert_eq!(directories, vec!["lib"]);
+            assert_eq!(counts(find(&summary.files, "main.js")), (1, 1, 0, 3));
+            assert_eq!(counts(&summary.total), (2, 2, 1, 5));
+            let _ = fs::remove_dir_all(root);
+        }
+
+        #[test]
+        fn aggregates_are_sorted_from_the_most_flagged() {
+            let (summary, root) = summarize(&[
+                ("a/one.js", "function f5() {}"),
+                ("b/two.js", "function f6() {}\nfunction f7() {}"),
+                ("c/low.js", "function f4() {}"),
+                ("d/high.js", "function f4() {}\nfunction f1() {}\nfunction f2() {}"),
+            ]);
+            let files: Vec<&str> = summary.files.iter().map(|aggregate| aggregate.path.as_str()).collect();
+            // By flagged count, then max score, then mean score, then path
+            assert_eq!(files, vec!["b/two.js", "a/one.js", "c/low.js", "d/high.js"]);
+            let directories: Vec<&str> = summary.directories.iter().map(|aggregate| aggregate.path.as_str()).collect();
+            assert_eq!(directories, vec!["b", "a", "c", "d"]);
+            let _ = fs::remove_dir_all(root);
+        }
+
+        #[test]
+        fn a_single_file_is_named_after_itself() {
+            let root = project("summary-file", &[("lib/math.js", "function f8() {}")]);
+            let file = root.join("lib/math.js");
+            let analyzed = Project::load(&file, &AnalysisOptions::default());
+            let modules = analyzed.modules();
+            let id = FunctionId { module: 0, function: 0 };
+            let summary = Summary::collect(&modules, &[(id, 8)], &[id], file.parent().unwrap());
+            assert_eq!(summary.files[0].path, "math.js");
+            assert!(summary.directories.is_empty());
+            assert_eq!(counts(&summary.total), (1, 1, 1, 8));
+            let _ = fs::remove_dir_all(root);
+        }
+    }
+}
@@ -1,83 +1,108 @@
 pub mod scoring {
-    use swc_common::Span;
-    use crate::common::create_config_directory;
-    use crate::visitor::{FunctionAnalysisVisitor, report};
-    use crate::visitor::max_scores::max_scores::*;
-
-    pub fn try_report_function(visitor: &FunctionAnalysisVisitor, span: Span) {
-        let metrics_score = _get_metrics_score(visitor);
-        if metrics_score > visitor.thresholds.average() {
-            report::report_function(span, &visitor.source_map, metrics_score);
-        }
+    use crate::Metrics;
+    use crate::visitor::FunctionAnalysis;
+    use crate::visitor::function_analysis::function_analysis::Recursion;
+    use crate::visitor::normalization::normalization::{scale_raw_score, NormalizationModel};
+
+    /// Score of the function if it is a WASM candidate. Whether it is one always depends on
+    /// the active thresholds, the reported score on the normalization model.
+    pub fn candidate_score(function: &FunctionAnalysis, thresholds: &Metrics, model: &NormalizationModel) -> Option<usize> {
+        let relative_score = scale_raw_score(raw_score(function, thresholds), max_raw_score(thresholds)).round() as usize;
+        (relative_score > thresholds.average()).then(|| model.score(function))
     }
 
     const WEIGHT_CYCLOMATIC: f32 = 0.5;
+    const WEIGHT_COGNITIVE: f32 = 0.5;
     const WEIGHT_ARITHMETIC: f32 = 1.0;
     const WEIGHT_LOOP: f32 = 1.5;
+    const WEIGHT_NUMERIC: f32 = 2.0;
+    const WEIGHT_ZERO_COPY: f32 = 1.5;
+    const WEIGHT_RECURSION: f32 = 1.5;
+
+    // Functions with a higher share of numeric data than this gain score, the rest lose it
+    const NUMERIC_SHARE_BASELINE: f32 = 0.5;
 
-    fn _get_metrics_score(visitor: &FunctionAnalysisVisitor) -> usize {
+    /// Weighted sum of how far each metric is above its threshold.
+    pub fn raw_score(function: &FunctionAnalysis, thresholds: &Metrics) -> f32 {
         // Calculate individual metric scores
-        let cyclomatic_score = _calculate_metric_score(visitor.cyclomatic_complexity as f32, visitor.thresholds.cyclomatic_complexity as f32, WEIGHT_CYCLOMATIC);
-        let loop_score = _calculate_metric_score(visitor.max_loop_depth as f32, visitor.thresholds.loop_depth as f32, WEIGHT_LOOP);
-        let arithmetic_score = _calculate_metric_score(visitor.arithmetic_operations as f32, visitor.thresholds.arithmetic_operations as f32, WEIGHT_ARITHMETIC);
+        let cyclomatic_score = _calculate_metric_score(function.cyclomatic_complexity as f32, thresholds.cyclomatic_complexity as f32, WEIGHT_CYCLOMATIC);
+        let cognitive_score = _calculate_metric_score(function.cognitive_complexity as f32, thresholds.cognitive_complexity as f32, WEIGHT_COGNITIVE);
+        let loop_score = _calculate_metric_score(function.effective_loop_depth() as f32, thresholds.loop_depth as f32, WEIGHT_LOOP);
+        let arithmetic_score = _calculate_metric_score(function.arithmetic_operations as f32, thresholds.arithmetic_operations as f32, WEIGHT_ARITHMETIC);
+        let numeric_score = _numeric_score(function);
+        // Shared ArrayBuffer memory makes the JS/WASM boundary cheap
+        let zero_copy_score = if function.typed_array_usage.is_zero_copy_candidate() { WEIGHT_ZERO_COPY } else { 0.0 };
+        // Recursive algorithms are hot paths even without loops
+        let recursion_score = if function.recursion == Recursion::None { 0.0 } else { WEIGHT_RECURSION };
 
-        let string_penalty = if visitor.string_operations > visitor.thresholds.string_operations {
-            2.0 * (visitor.string_operations as f32 - visitor.thresholds.string_operations as f32)
+        let string_penalty = if function.string_operations > thresholds.string_operations {
+            2.0 * (function.string_operations as f32 - thresholds.string_operations as f32)
         } else {
             0.0
         };
 
-        // Sum the maximum scores
-        let max_possible_score = _get_max_possible_score(visitor);
-
-        // Normalize the final score
-        let raw_score = cyclomatic_score + loop_score + arithmetic_score - string_penalty;
-        let normalized_score = (raw_score as f32 / max_possible_score * 10.0).min(10.0).round() as usize;
+        cyclomatic_score + cognitive_score + loop_score + arithmetic_score + numeric_score + zero_copy_score + recursion_score - string_penalty
+    }
 
-        normalized_score
+    /// The metric contributing the most to the raw score, like `loop depth 3 (threshold 1)`.
+    pub fn top_reason(function: &FunctionAnalysis, thresholds: &Metrics) -> String {
+        let loop_depth = function.effective_loop_depth();
+        let contributions = [
+            (
+                _calculate_metric_score(function.cyclomatic_complexity as f32, thresholds.cyclomatic_complexity as f32, WEIGHT_CYCLOMATIC),
+                format!("cyclomatic complexity {} (threshold {})", function.cyclomatic_complexity, thresholds.cyclomatic_complexity),
+            ),
+            (
+                _calculate_metric_score(function.cognitive_complexity as f32, thresholds.cognitive_complexity as f32, WEIGHT_COGNITIVE),
+                format!("cognitive complexity {} (threshold {})", function.cognitive_complexity, thresholds.cognitive_complexity),
+            ),
+            (
+                _calculate_metric_score(loop_depth as f32, thresholds.loop_depth as f32, WEIGHT_LOOP),
+                format!("loop depth {} (threshold {})", loop_depth, thresholds.loop_depth),
+            ),
+            (
+                _calculate_metric_score(function.arithmetic_operations as f32, thresholds.arithmetic_operations as f32, WEIGHT_ARITHMETIC),
+                format!("{} arithmetic operations (threshold {})", function.arithmetic_operations, thresholds.arithmetic_operations),
+            ),
+            (
+                _numeric_score(function),
+                match function.type_summary.numeric_share() {
+                    Some(numeric_share) => format!("{:.0}% numeric data", numeric_share * 100.0),
+                    None => "no inferred data types".to_string(),
+                },
+            ),
+            (
+                if function.typed_array_usage.is_zero_copy_candidate() { WEIGHT_ZERO_COPY } else { 0.0 },
+                "typed arrays shareable without copying".to_string(),
+            ),
+            (
+                if function.recursion == Recursion::None { 0.0 } else { WEIGHT_RECURSION },
+                "recursion".to_string(),
+            ),
+        ];
+        contributions.into_iter()
+            .max_by(|(a, _), (b, _)| a.total_cmp(b))
+            .map(|(_, reason)| reason)
+            .unwrap_or_default()
     }
 
-    fn _get_max_possible_score(visitor: &FunctionAnalysisVisitor) -> f32 {
-        // Attempt to load existing max scores
-        let max_possible_score = match create_config_directory() {
-            Ok(config_dir) => {
-                if let Some(loaded_scores) = load_max_scores(config_dir.clone()) {
-                    // Calculate the total max possible score from loaded scores
-                    loaded_scores.max_cyclomatic_score +
-                        loaded_scores.max_loop_score +
-                        loaded_scores.max_arithmetic_score -
-                        loaded_scores.max_string_penalty
-                } else {
-                    // If loading is unsuccessful, calculate and store new max scores
-                    let max_cyclomatic_score = _calculate_max_metric_score(visitor.thresholds.cyclomatic_complexity, WEIGHT_CYCLOMATIC);
-                    let max_loop_score = _calculate_max_metric_score(visitor.thresholds.loop_depth, WEIGHT_LOOP);
-                    let max_arithmetic_score = _calculate_max_metric_score(visitor.thresholds.arithmetic_operations, WEIGHT_ARITHMETIC);
-                    let max_string_penalty = 2.0 * visitor.thresholds.string_operations as f32;
-
-                    let max_scores = MaxScores {
-                        max_cyclomatic_score,
-                        max_loop_score,
-                        max_arithmetic_score,
-                        max_string_penalty,
-                    };
-
-                    if let Err(e) = store_max_scores(&max_scores, config_dir) {
-                        eprintln!("Error writing max scores: {}", e);
-                    }
-
-                    // total max possible score
-                    max_cyclomatic_score + max_loop_score + max_arithmetic_score - max_string_penalty
-                }
-            },
-            Err(e) => {
-                eprintln!("Error creating configuration directory: {}", e);
-                0.0 // Default value if unable to create directory or load scores
-            },
-        };
+    /// Raw score corresponding to 10/10 under the given thresholds.
+    pub fn max_raw_score(thresholds: &Metrics) -> f32 {
+        let max_cyclomatic_score = _calculate_max_metric_score(thresholds.cyclomatic_complexity, WEIGHT_CYCLOMATIC);
+        let max_cognitive_score = _calculate_max_metric_score(thresholds.cognitive_complexity, WEIGHT_COGNITIVE);
+        let max_loop_score = _calculate_max_metric_score(thresholds.loop_depth, WEIGHT_LOOP);
+        let max_arithmetic_score = _calculate_max_metric_score(thresholds.arithmetic_operations, WEIGHT_ARITHMETIC);
+        let max_string_penalty = 2.0 * thresholds.string_operations as f32;
+        let max_numeric_score = _calculate_metric_score(1.0, NUMERIC_SHARE_BASELINE, WEIGHT_NUMERIC);
 
-        return max_possible_score
+        max_cyclomatic_score + max_cognitive_score + max_loop_score + max_arithmetic_score + max_numeric_score + WEIGHT_ZERO_COPY + WEIGHT_RECURSION - max_string_penalty
     }
 
+    // Without any typed binding there is no evidence either way
+    fn _numeric_score(function: &FunctionAnalysis) -> f32 {
+        function.type_summary.numeric_share()
+            .map_or(0.0, |numeric_share| _calculate_metric_score(numeric_share, NUMERIC_SHARE_BASELINE, WEIGHT_NUMERIC))
+    }
 
     fn _calculate_metric_score(actual: f32, threshold: f32, weight: f32) -> f32 {
         (actual - threshold) * weight
@@ -86,4 +111,47 @@ pub mod scoring {
     fn _calculate_max_metric_score(threshold: usize, weight: f32) -> f32 {
         threshold as f32 * weight
     }
-}
\ No newline at end of file
+
+    #[cfg(test)]
+    mod tests {
+        use crate::fixtures::fixtures::{analyze, ZERO_THRESHOLDS};
+        use crate::visitor::FunctionAnalysis;
+        use super::{raw_score, top_reason};
+
+        fn function_of(source: &str) -> FunctionAnalysis {
+            analyze(source).remove(0)
+        }
+
+        #[test]
+        fn function_without_bindings_has_no_numeric_term() {
+            let function = function_of("function f() { return g(); }");
+            assert_eq!(function.type_summary.numeric_share(), None);
+            // Only cyclomatic complexity 1 with weight 0.5
+            assert_eq!(raw_score(&function, &ZERO_THRESHOLDS), 0.5);
+        }
+
+        #[test]
+        fn function_with_only_unknown_bindings_has_no_numeric_term() {
+            let function = function_of("function f(a, b) { return g(a, b); }");
+            assert_eq!(function.type_summary.unknown, 2);
+            assert_eq!(function.type_summary.numeric_share(), None);
+            assert_eq!(raw_score(&function, &ZERO_THRESHOLDS), 0.5);
+            assert_eq!(top_reason(&function, &ZERO_THRESHOLDS), "cyclomatic complexity 1 (threshold 0)");
+        }
+
+        #[test]
+        fn numeric_bindings_raise_the_score() {
+            let function = function_of("function f() { const a = 1; return g(a); }");
+            assert_eq!(function.type_summary.numeric_share(), Some(1.0));
+            // (1.0 - 0.5) * 2.0 on top of the cyclomatic term
+            assert_eq!(raw_score(&function, &ZERO_THRESHOLDS), 1.5);
+        }
+
+        #[test]
+        fn non_numeric_bindings_lower_the_score() {
+            let function = function_of("function f() { const a = {}; return g(a); }");
+            assert_eq!(function.type_summary.numeric_share(), Some(0.0));
+            assert_eq!(raw_score(&function, &ZERO_THRESHOLDS), -0.5);
+        }
+    }
+}
@@ -55,12 +55,9 @@ pub mod string_counter {
     fn is_string_method_call(call_expr: &CallExpr) -> bool {
         // First match against Callee
         if let Callee::Expr(expr) = &call_expr.callee {
-            // Then check if the Expr is a MemberExpr
-            if let Expr::Member(MemberExpr { obj: _, prop, .. }) = &**expr {
-                // Check if the property is an Ident (an identifier)
-                if let MemberProp::Ident(Ident { sym, .. }) = prop {
-                    return STRING_METHODS.contains(&sym.as_ref());
-                }
+            // Then check if the Expr is a MemberExpr whose property is an Ident (an identifier)
+            if let Expr::Member(MemberExpr { obj: _, prop: MemberProp::Ident(Ident { sym, .. }), .. }) = &**expr {
+                return STRING_METHODS.contains(&sym.as_ref());
             }
         }
         false
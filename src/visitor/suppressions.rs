@@ -0,0 +1,220 @@
+pub mod suppressions {
+    use std::cell::Cell;
+    use swc_common::comments::{Comment, CommentKind, SingleThreadedComments};
+    use swc_common::{BytePos, SourceMap, Span};
+
+    const IGNORE_NEXT_LINE: &str = "wasm-grate-ignore-next-line";
+    const DISABLE: &str = "wasm-grate-disable";
+    const ENABLE: &str = "wasm-grate-enable";
+    const JSDOC_IGNORE: &str = "@wasm-grate-ignore";
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub enum SuppressionKind {
+        /// `// wasm-grate-ignore-next-line`
+        NextLine,
+        /// `/* wasm-grate-disable */` up to the next `/* wasm-grate-enable */` or the end of the file
+        Region { end: Option<BytePos> },
+        /// `@wasm-grate-ignore` in the JSDoc block of a function
+        JsDoc,
+    }
+
+    impl SuppressionKind {
+        pub fn directive(&self) -> &'static str {
+            match self {
+                SuppressionKind::NextLine => IGNORE_NEXT_LINE,
+                SuppressionKind::Region { .. } => DISABLE,
+                SuppressionKind::JsDoc => JSDOC_IGNORE,
+            }
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct Suppression {
+        pub kind: SuppressionKind,
+        /// Span of the comment holding the directive
+        pub span: Span,
+        /// Line the comment ends on
+        line: usize,
+        /// Code follows the comment on that line
+        inline: bool,
+        used: Cell<bool>,
+    }
+
+    /// Suppression comments of one source file.
+    #[derive(Clone, Debug, Default)]
+    pub struct Suppressions {
+        suppressions: Vec<Suppression>,
+    }
+
+    impl Suppressions {
+        pub fn collect(comments: &SingleThreadedComments, source_map: &SourceMap) -> Self {
+            let (leading, trailing) = comments.borrow_all();
+            let mut all_comments: Vec<&Comment> = leading.values().chain(trailing.values()).flatten().collect();
+            all_comments.sort_by_key(|comment| comment.span.lo);
+            all_comments.dedup_by_key(|comment| comment.span);
+
+            let mut suppressions: Vec<Suppression> = Vec::new();
+            let mut open_region: Option<usize> = None;
+            for comment in all_comments {
+                let mut words = comment.text.split_whitespace();
+                let kind = match words.next() {
+                    Some(IGNORE_NEXT_LINE) => SuppressionKind::NextLine,
+                    Some(DISABLE) if open_region.is_none() => SuppressionKind::Region { end: None },
+                    Some(ENABLE) => {
+                        if let Some(index) = open_region.take() {
+                            suppressions[index].kind = SuppressionKind::Region { end: Some(comment.span.lo) };
+                        }
+                        continue;
+                    }
+                    _ if _is_jsdoc(comment) && comment.text.split_whitespace().any(|word| word == JSDOC_IGNORE) => {
+                        SuppressionKind::JsDoc
+                    }
+                    _ => continue,
+                };
+
+                if matches!(kind, SuppressionKind::Region { .. }) {
+                    open_region = Some(suppressions.len());
+                }
+                let end = source_map.lookup_char_pos(comment.span.hi);
+                let inline = end.file.get_line(end.line - 1)
+                    .is_some_and(|line| line.chars().skip(end.col.0).any(|c| !c.is_whitespace()));
+                suppressions.push(Suppression {
+                    kind,
+                    span: comment.span,
+                    line: end.line,
+                    inline,
+                    used: Cell::new(false),
+                });
+            }
+            Suppressions { suppressions }
+        }
+
+        /// Whether a finding for the function at `span` is suppressed, marking the suppressions applied as used.
+        pub fn suppresses(&self, span: Span, source_map: &SourceMap) -> bool {
+            let line = source_map.lookup_char_pos(span.lo).line;
+            let mut suppressed = false;
+            for suppression in &self.suppressions {
+                let applies = match suppression.kind {
+                    SuppressionKind::NextLine => suppression.line + 1 == line,
+                    SuppressionKind::Region { end } => {
+                        suppression.span.hi <= span.lo && end.is_none_or(|end| span.lo < end)
+                    }
+                    // A JSDoc block documents the code right after it, on its own line or the next one
+                    SuppressionKind::JsDoc if suppression.inline => suppression.line == line && suppression.span.hi <= span.lo,
+                    SuppressionKind::JsDoc => suppression.line + 1 == line,
+                };
+                if applies {
+                    suppression.used.set(true);
+                    suppressed = true;
+                }
+            }
+            suppressed
+        }
+
+        /// Suppressions that did not hide any finding.
+        pub fn unused(&self) -> impl Iterator<Item = &Suppression> {
+            self.suppressions.iter().filter(|suppression| !suppression.used.get())
+        }
+    }
+
+    fn _is_jsdoc(comment: &Comment) -> bool {
+        comment.kind == CommentKind::Block && comment.text.starts_with('*')
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use swc_common::comments::SingleThreadedComments;
+        use swc_common::{FileName, SourceMap};
+        use swc_ecma_parser::Syntax;
+        use crate::fixtures::fixtures::{analyze_module, parse_at};
+        use super::Suppressions;
+
+        /// Names of the suppressed functions, then the directives of the unused suppressions.
+        fn suppress(source: &str) -> (Vec<String>, Vec<&'static str>) {
+            let source_map = SourceMap::default();
+            let file = source_map.new_source_file(FileName::Anon, source.to_string());
+            let comments = SingleThreadedComments::default();
+            let functions = analyze_module(&parse_at(source, Syntax::default(), file.start_pos, Some(&comments)));
+
+            let suppressions = Suppressions::collect(&comments, &source_map);
+            let suppressed = functions.iter()
+                .filter(|function| suppressions.suppresses(function.span, &source_map))
+                .map(|function| function.display_name().to_string())
+                .collect();
+            let unused = suppressions.unused().map(|suppression| suppression.kind.directive()).collect();
+            (suppressed, unused)
+        }
+
+        #[test]
+        fn next_line_suppresses_only_the_following_line() {
+            let (suppressed, unused) = suppress("// wasm-grate-ignore-next-line\nfunction a() {}\nfunction b() {}");
+            assert_eq!(suppressed, vec!["a"]);
+            assert!(unused.is_empty());
+        }
+
+        #[test]
+        fn next_line_before_a_blank_line_is_unused() {
+            let (suppressed, unused) = suppress("// wasm-grate-ignore-next-line\n\nfunction a() {}");
+            assert!(suppressed.is_empty());
+            assert_eq!(unused, vec!["wasm-grate-ignore-next-line"]);
+        }
+
+        #[test]
+        fn region_suppresses_up_to_enable() {
+            let source = "function a() {}\n\
+                          /* wasm-grate-disable */\n\
+                          function b() {}\n\
+                          const c = () => {};\n\
+                          /* wasm-grate-enable */\n\
+                          function d() {}";
+            assert_eq!(suppress(source).0, vec!["b", "c"]);
+        }
+
+        #[test]
+        fn unclosed_region_suppresses_to_the_end_of_the_file() {
+            let source = "function a() {}\n// wasm-grate-disable\nfunction b() {}\n\nfunction c() {}";
+            assert_eq!(suppress(source).0, vec!["b", "c"]);
+        }
+
+        #[test]
+        fn enable_closes_regions_without_nesting() {
+            let source = "/* wasm-grate-disable */\n\
+                          /* wasm-grate-disable */\n\
+                          function a() {}\n\
+                          /* wasm-grate-enable */\n\
+                          function b() {}";
+            let (suppressed, unused) = suppress(source);
+            assert_eq!(suppressed, vec!["a"]);
+            assert!(unused.is_empty());
+        }
+
+        #[test]
+        fn jsdoc_ignore_suppresses_the_documented_function() {
+            let source = "/**\n * Runs once per page load.\n * @wasm-grate-ignore\n */\n\
+                          const parseConfig = (text) => text;\n\
+                          /** @wasm-grate-ignore */ function inline() {}\n\
+                          function other() {}";
+            assert_eq!(suppress(source).0, vec!["parseConfig", "inline"]);
+        }
+
+        #[test]
+        fn ignore_tag_outside_jsdoc_is_not_a_suppression() {
+            let (suppressed, unused) = suppress("/* @wasm-grate-ignore */\nfunction a() {}\n// @wasm-grate-ignore\nfunction b() {}");
+            assert!(suppressed.is_empty());
+            assert!(unused.is_empty());
+        }
+
+        #[test]
+        fn suppressions_without_functions_are_unused() {
+            let source = "/** @wasm-grate-ignore */\n\
+                          const limit = 10;\n\
+                          /* wasm-grate-disable */\n\
+                          /* wasm-grate-enable */\n\
+                          // wasm-grate-ignore-next-line\n\
+                          function a() {}";
+            let (suppressed, unused) = suppress(source);
+            assert_eq!(suppressed, vec!["a"]);
+            assert_eq!(unused, vec!["@wasm-grate-ignore", "wasm-grate-disable"]);
+        }
+    }
+}
@@ -0,0 +1,494 @@
+pub mod type_inference {
+    use std::collections::HashMap;
+    use swc_ecma_ast::{
+        ArrowExpr, AssignExpr, AssignOp, BinExpr, BinaryOp, Callee, Expr, Function, Lit, MemberProp,
+        ObjectPatProp, Pat, PatOrExpr, TsEntityName, TsKeywordTypeKind, TsType, UnaryOp, UpdateExpr, VarDeclarator,
+    };
+    use swc_ecma_visit::{Visit, VisitWith};
+
+    /// Constructors whose instances are backed by a raw `ArrayBuffer`.
+    pub const TYPED_ARRAY_CONSTRUCTORS: [&str; 13] = [
+        "Int8Array", "Uint8Array", "Uint8ClampedArray", "Int16Array", "Uint16Array",
+        "Int32Array", "Uint32Array", "Float32Array", "Float64Array", "BigInt64Array",
+        "BigUint64Array", "ArrayBuffer", "DataView",
+    ];
+
+    const INT_MATH_METHODS: [&str; 5] = ["floor", "ceil", "round", "trunc", "sign"];
+
+    #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+    pub enum InferredType {
+        Int,
+        Float,
+        TypedArray,
+        String,
+        Object,
+        Unknown,
+    }
+
+    impl InferredType {
+        pub fn is_numeric(self) -> bool {
+            matches!(self, InferredType::Int | InferredType::Float | InferredType::TypedArray)
+        }
+    }
+
+    /// Number of parameters and local bindings of a function per inferred type.
+    #[derive(Clone, Copy, Default, Debug)]
+    pub struct TypeSummary {
+        pub int: usize,
+        pub float: usize,
+        pub typed_array: usize,
+        pub string: usize,
+        pub object: usize,
+        pub unknown: usize,
+    }
+
+    impl TypeSummary {
+        pub fn total(&self) -> usize {
+            self.int + self.float + self.typed_array + self.string + self.object + self.unknown
+        }
+
+        /// Share of bindings (0.0 - 1.0) that hold numbers or typed arrays,
+        /// `None` when no binding has a known type to tell.
+        pub fn numeric_share(&self) -> Option<f32> {
+            let total = self.total();
+            (total > self.unknown).then(|| (self.int + self.float + self.typed_array) as f32 / total as f32)
+        }
+    }
+
+    /// Classifies the parameters and local variables of a single function.
+    /// The inference is flow-insensitive: a binding keeps the first known type it gets
+    /// from an annotation, an initializer, an assignment or its use in arithmetic.
+    pub fn infer_function_types<'p, B>(params: impl IntoIterator<Item = &'p Pat>, body: &B) -> TypeSummary
+    where
+        B: VisitWith<TypeInferenceVisitor>,
+    {
+        let mut visitor = TypeInferenceVisitor::default();
+        for param in params {
+            visitor.declare_pattern(param, InferredType::Unknown);
+        }
+        body.visit_with(&mut visitor);
+
+        let mut summary = TypeSummary::default();
+        for inferred in visitor.bindings.values() {
+            match inferred {
+                InferredType::Int => summary.int += 1,
+                InferredType::Float => summary.float += 1,
+                InferredType::TypedArray => summary.typed_array += 1,
+                InferredType::String => summary.string += 1,
+                InferredType::Object => summary.object += 1,
+                InferredType::Unknown => summary.unknown += 1,
+            }
+        }
+        summary
+    }
+
+    #[derive(Default)]
+    pub struct TypeInferenceVisitor {
+        bindings: HashMap<String, InferredType>,
+    }
+
+    impl TypeInferenceVisitor {
+        fn declare_pattern(&mut self, pat: &Pat, from_init: InferredType) {
+            match pat {
+                Pat::Ident(binding) => {
+                    let annotated = binding.type_ann.as_ref()
+                        .map_or(InferredType::Unknown, |ann| infer_ts_type(&ann.type_ann));
+                    let inferred = if annotated == InferredType::Unknown { from_init } else { annotated };
+                    self.bindings.insert(binding.id.sym.to_string(), inferred);
+                }
+                Pat::Assign(assign_pat) => {
+                    let from_default = self.infer_expr(&assign_pat.right);
+                    self.declare_pattern(&assign_pat.left, from_default);
+                }
+                Pat::Rest(rest_pat) => self.declare_pattern(&rest_pat.arg, InferredType::Object),
+                Pat::Array(array_pat) => {
+                    for elem in array_pat.elems.iter().flatten() {
+                        self.declare_pattern(elem, InferredType::Unknown);
+                    }
+                }
+                Pat::Object(object_pat) => {
+                    for prop in &object_pat.props {
+                        match prop {
+                            ObjectPatProp::Assign(assign_prop) => {
+                                let from_default = assign_prop.value.as_ref()
+                                    .map_or(InferredType::Unknown, |value| self.infer_expr(value));
+                                self.bindings.insert(assign_prop.key.sym.to_string(), from_default);
+                            }
+                            ObjectPatProp::KeyValue(key_value) => self.declare_pattern(&key_value.value, InferredType::Unknown),
+                            ObjectPatProp::Rest(rest_pat) => self.declare_pattern(&rest_pat.arg, InferredType::Object),
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        /// Upgrades a binding whose type is still unknown.
+        fn refine(&mut self, expr: &Expr, inferred: InferredType) {
+            if inferred == InferredType::Unknown {
+                return;
+            }
+            if let Expr::Ident(ident) = expr.unwrap_parens() {
+                if let Some(current) = self.bindings.get_mut(ident.sym.as_ref()) {
+                    if *current == InferredType::Unknown {
+                        *current = inferred;
+                    }
+                }
+            }
+        }
+
+        fn infer_expr(&self, expr: &Expr) -> InferredType {
+            match expr.unwrap_parens() {
+                Expr::Lit(Lit::Num(number)) => {
+                    let is_integer = number.value.fract() == 0.0
+                        && number.raw.as_ref().is_none_or(|raw| !raw.contains('.'));
+                    if is_integer { InferredType::Int } else { InferredType::Float }
+                }
+                Expr::Lit(Lit::BigInt(_)) => InferredType::Int,
+                Expr::Lit(Lit::Str(_)) | Expr::Tpl(_) => InferredType::String,
+                Expr::Object(_) | Expr::Array(_) | Expr::Lit(Lit::Regex(_)) => InferredType::Object,
+                Expr::Ident(ident) => self.bindings.get(ident.sym.as_ref()).copied().unwrap_or(InferredType::Unknown),
+                Expr::New(new_expr) => match &*new_expr.callee {
+                    Expr::Ident(ident) if TYPED_ARRAY_CONSTRUCTORS.contains(&ident.sym.as_ref()) => InferredType::TypedArray,
+                    _ => InferredType::Object,
+                },
+                Expr::Unary(unary_expr) => match unary_expr.op {
+                    UnaryOp::Minus | UnaryOp::Plus => InferredType::Float,
+                    UnaryOp::Tilde => InferredType::Int,
+                    UnaryOp::TypeOf => InferredType::String,
+                    _ => InferredType::Unknown,
+                },
+                Expr::Update(_) => InferredType::Int,
+                Expr::Bin(bin_expr) => self.infer_binary(bin_expr),
+                Expr::Cond(cond_expr) => {
+                    let cons = self.infer_expr(&cond_expr.cons);
+                    if cons == self.infer_expr(&cond_expr.alt) { cons } else { InferredType::Unknown }
+                }
+                Expr::Member(member_expr) => match &member_expr.prop {
+                    MemberProp::Ident(prop) if prop.sym.as_ref() == "length" => InferredType::Int,
+                    // Indexing a typed array yields a number
+                    MemberProp::Computed(_) if self.infer_expr(&member_expr.obj) == InferredType::TypedArray => InferredType::Float,
+                    _ => InferredType::Unknown,
+                },
+                Expr::Call(call_expr) => match &call_expr.callee {
+                    Callee::Expr(callee) => _infer_call(callee),
+                    _ => InferredType::Unknown,
+                },
+                Expr::TsAs(as_expr) => infer_ts_type(&as_expr.type_ann),
+                _ => InferredType::Unknown,
+            }
+        }
+
+        fn infer_binary(&self, bin_expr: &BinExpr) -> InferredType {
+            match bin_expr.op {
+                BinaryOp::Add => {
+                    let left = self.infer_expr(&bin_expr.left);
+                    let right = self.infer_expr(&bin_expr.right);
+                    if left == InferredType::String || right == InferredType::String {
+                        InferredType::String
+                    } else if left == InferredType::Int && right == InferredType::Int {
+                        InferredType::Int
+                    } else if left.is_numeric() && right.is_numeric() {
+                        InferredType::Float
+                    } else {
+                        InferredType::Unknown
+                    }
+                }
+                BinaryOp::Sub | BinaryOp::Mul | BinaryOp::Mod => {
+                    let left = self.infer_expr(&bin_expr.left);
+                    let right = self.infer_expr(&bin_expr.right);
+                    if left == InferredType::Int && right == InferredType::Int { InferredType::Int } else { InferredType::Float }
+                }
+                BinaryOp::Div | BinaryOp::Exp => InferredType::Float,
+                BinaryOp::BitOr | BinaryOp::BitAnd | BinaryOp::BitXor
+                | BinaryOp::LShift | BinaryOp::RShift | BinaryOp::ZeroFillRShift => InferredType::Int,
+                _ => InferredType::Unknown,
+            }
+        }
+    }
+
+    impl Visit for TypeInferenceVisitor {
+        // Nested functions get their own inference
+        fn visit_function(&mut self, _: &Function) {}
+        fn visit_arrow_expr(&mut self, _: &ArrowExpr) {}
+
+        fn visit_var_declarator(&mut self, n: &VarDeclarator) {
+            n.init.visit_with(self);
+            let from_init = n.init.as_ref().map_or(InferredType::Unknown, |init| self.infer_expr(init));
+            self.declare_pattern(&n.name, from_init);
+        }
+
+        fn visit_assign_expr(&mut self, n: &AssignExpr) {
+            n.visit_children_with(self);
+            let target = match &n.left {
+                PatOrExpr::Expr(expr) => Some(&**expr),
+                PatOrExpr::Pat(pat) => match &**pat {
+                    Pat::Expr(expr) => Some(&**expr),
+                    _ => None,
+                },
+            };
+            let assigned = match n.op {
+                AssignOp::Assign => self.infer_expr(&n.right),
+                AssignOp::AddAssign => match self.infer_expr(&n.right) {
+                    InferredType::String => InferredType::String,
+                    inferred if inferred.is_numeric() => InferredType::Float,
+                    _ => InferredType::Unknown,
+                },
+                AssignOp::SubAssign | AssignOp::MulAssign | AssignOp::DivAssign
+                | AssignOp::ModAssign | AssignOp::ExpAssign => InferredType::Float,
+                AssignOp::BitOrAssign | AssignOp::BitAndAssign | AssignOp::BitXorAssign
+                | AssignOp::LShiftAssign | AssignOp::RShiftAssign | AssignOp::ZeroFillRShiftAssign => InferredType::Int,
+                _ => InferredType::Unknown,
+            };
+            match (target, &n.left) {
+                (Some(expr), _) => self.refine(expr, assigned),
+                (None, PatOrExpr::Pat(pat)) => {
+                    if let Pat::Ident(binding) = &**pat {
+                        if let Some(current) = self.bindings.get_mut(binding.id.sym.as_ref()) {
+                            if *current == InferredType::Unknown {
+                                *current = assigned;
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        fn visit_bin_expr(&mut self, n: &BinExpr) {
+            n.visit_children_with(self);
+            // Operands of purely numeric operators are numbers themselves
+            let operand_type = match n.op {
+                BinaryOp::Sub | BinaryOp::Mul | BinaryOp::Div | BinaryOp::Mod | BinaryOp::Exp => InferredType::Float,
+                BinaryOp::BitOr | BinaryOp::BitAnd | BinaryOp::BitXor
+                | BinaryOp::LShift | BinaryOp::RShift | BinaryOp::ZeroFillRShift => InferredType::Int,
+                _ => InferredType::Unknown,
+            };
+            self.refine(&n.left, operand_type);
+            self.refine(&n.right, operand_type);
+        }
+
+        fn visit_update_expr(&mut self, n: &UpdateExpr) {
+            n.visit_children_with(self);
+            self.refine(&n.arg, InferredType::Int);
+        }
+    }
+
+    /// Result type of calls to well-known numeric and string builtins.
+    fn _infer_call(callee: &Expr) -> InferredType {
+        match callee {
+            Expr::Ident(ident) => match ident.sym.as_ref() {
+                "parseInt" => InferredType::Int,
+                "parseFloat" | "Number" => InferredType::Float,
+                "String" => InferredType::String,
+                _ => InferredType::Unknown,
+            },
+            Expr::Member(member_expr) => match (&*member_expr.obj, &member_expr.prop) {
+                (Expr::Ident(obj), MemberProp::Ident(prop)) if obj.sym.as_ref() == "Math" => {
+                    if INT_MATH_METHODS.contains(&prop.sym.as_ref()) { InferredType::Int } else { InferredType::Float }
+                }
+                (_, MemberProp::Ident(prop)) => match prop.sym.as_ref() {
+                    "toFixed" | "toString" | "join" | "toUpperCase" | "toLowerCase" | "trim" => InferredType::String,
+                    "indexOf" | "charCodeAt" | "findIndex" => InferredType::Int,
+                    _ => InferredType::Unknown,
+                },
+                _ => InferredType::Unknown,
+            },
+            _ => InferredType::Unknown,
+        }
+    }
+
+    /// Maps a TypeScript annotation onto the inferred type lattice.
+    pub fn infer_ts_type(ts_type: &TsType) -> InferredType {
+        match ts_type {
+            TsType::TsKeywordType(keyword) => match keyword.kind {
+                TsKeywordTypeKind::TsNumberKeyword => InferredType::Float,
+                TsKeywordTypeKind::TsBigIntKeyword => InferredType::Int,
+                TsKeywordTypeKind::TsStringKeyword => InferredType::String,
+                TsKeywordTypeKind::TsObjectKeyword => InferredType::Object,
+                _ => InferredType::Unknown,
+            },
+            TsType::TsTypeRef(type_ref) => match &type_ref.type_name {
+                TsEntityName::Ident(ident) if TYPED_ARRAY_CONSTRUCTORS.contains(&ident.sym.as_ref()) => InferredType::TypedArray,
+                _ => InferredType::Object,
+            },
+            // Arrays are classified by their element type, `number[]` is numeric data
+            TsType::TsArrayType(array_type) => match infer_ts_type(&array_type.elem_type) {
+                InferredType::Int | InferredType::Float => InferredType::Float,
+                _ => InferredType::Object,
+            },
+            TsType::TsParenthesizedType(paren_type) => infer_ts_type(&paren_type.type_ann),
+            TsType::TsTypeLit(_) | TsType::TsTupleType(_) | TsType::TsFnOrConstructorType(_) => InferredType::Object,
+            _ => InferredType::Unknown,
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use swc_ecma_parser::Syntax;
+        use swc_ecma_visit::VisitWith;
+        use crate::fixtures::fixtures::{first_function, parse_with};
+        use super::{infer_function_types, InferredType, TypeInferenceVisitor};
+        use super::InferredType::{Float, Int, Object, String, TypedArray, Unknown};
+
+        /// Type inferred for `binding` in the function the TypeScript fixture starts with.
+        fn type_of(source: &str, binding: &str) -> InferredType {
+            let module = parse_with(source, Syntax::Typescript(Default::default()));
+            let function = &first_function(&module).function;
+            let mut visitor = TypeInferenceVisitor::default();
+            for param in &function.params {
+                visitor.declare_pattern(&param.pat, Unknown);
+            }
+            function.body.as_ref().unwrap().visit_with(&mut visitor);
+            *visitor.bindings.get(binding).unwrap_or_else(|| panic!("No binding {} in {}", binding, source))
+        }
+
+        fn initialized_with(init: &str) -> InferredType {
+            type_of(&format!("function f(a, s) {{ const x = {}; }}", init), "x")
+        }
+
+        #[test]
+        fn literals_have_their_own_type() {
+            for (init, expected) in [
+                ("1", Int),
+                ("0x10", Int),
+                ("1e3", Int),
+                ("10n", Int),
+                ("1.5", Float),
+                ("1.0", Float),
+                ("'text'", String),
+                ("`t${a}`", String),
+                ("{}", Object),
+                ("[1, 2]", Object),
+                ("/re/", Object),
+                ("true", Unknown),
+                ("null", Unknown),
+            ] {
+                assert_eq!(initialized_with(init), expected, "case {:?}", init);
+            }
+        }
+
+        #[test]
+        fn arithmetic_keeps_integers_only_when_both_sides_are() {
+            for (init, expected) in [
+                ("1 + 2", Int),
+                ("1 + 2.5", Float),
+                ("1 - 2", Int),
+                ("3 * 0.5", Float),
+                ("4 / 2", Float),
+                ("2 ** 3", Float),
+                ("a | 0", Int),
+                ("a >>> 1", Int),
+                ("~a", Int),
+                ("-1", Float),
+                ("'n' + 1", String),
+                ("a + 1", Unknown),
+                ("a ? 1 : 2", Int),
+                ("a ? 1 : 'one'", Unknown),
+                ("(1 + 2)", Int),
+            ] {
+                assert_eq!(initialized_with(init), expected, "case {:?}", init);
+            }
+        }
+
+        #[test]
+        fn operands_of_numeric_operators_become_numbers() {
+            assert_eq!(type_of("function f(a, b) { return a * b; }", "a"), Float);
+            assert_eq!(type_of("function f(a, b) { return a & b; }", "b"), Int);
+            assert_eq!(type_of("function f(i) { i++; }", "i"), Int);
+            // `+` also joins strings, it tells nothing
+            assert_eq!(type_of("function f(a, b) { return a + b; }", "a"), Unknown);
+        }
+
+        #[test]
+        fn first_known_type_wins() {
+            assert_eq!(type_of("function f() { let x; x = 'a'; x = 1; }", "x"), String);
+            assert_eq!(type_of("function f(a) { let x = 1; x = a * 'b'; }", "x"), Int);
+            assert_eq!(type_of("function f() { let x; x |= 3; }", "x"), Int);
+            assert_eq!(type_of("function f() { let x; x /= 3; }", "x"), Float);
+        }
+
+        #[test]
+        fn typed_arrays_and_their_elements() {
+            assert_eq!(initialized_with("new Float32Array(4)"), TypedArray);
+            assert_eq!(initialized_with("new DataView(a)"), TypedArray);
+            assert_eq!(initialized_with("new Map()"), Object);
+            assert_eq!(type_of("function f() { const pixels = new Uint8Array(4); const p = pixels[0]; }", "p"), Float);
+            assert_eq!(type_of("function f(list) { const p = list[0]; }", "p"), Unknown);
+            assert_eq!(initialized_with("a.length"), Int);
+        }
+
+        #[test]
+        fn math_and_builtin_calls() {
+            for (init, expected) in [
+                ("Math.floor(a)", Int),
+                ("Math.round(a)", Int),
+                ("Math.sqrt(a)", Float),
+                ("Math.random()", Float),
+                ("parseInt(s)", Int),
+                ("parseFloat(s)", Float),
+                ("Number(s)", Float),
+                ("String(a)", String),
+                ("a.toFixed(2)", String),
+                ("s.charCodeAt(0)", Int),
+                ("compute(a)", Unknown),
+                ("a.compute()", Unknown),
+            ] {
+                assert_eq!(initialized_with(init), expected, "case {:?}", init);
+            }
+        }
+
+        #[test]
+        fn typescript_annotations_take_precedence() {
+            for (params, binding, expected) in [
+                ("x: number", "x", Float),
+                ("x: bigint", "x", Int),
+                ("x: string", "x", String),
+                ("x: object", "x", Object),
+                ("x: Float64Array", "x", TypedArray),
+                ("x: Point", "x", Object),
+                ("x: number[]", "x", Float),
+                ("x: string[]", "x", Object),
+                ("x: (number)", "x", Float),
+                ("x: [number, number]", "x", Object),
+                ("x: { a: number }", "x", Object),
+                ("x: any", "x", Unknown),
+                ("x: boolean", "x", Unknown),
+                ("x = 1.5", "x", Float),
+                ("x: string = 1", "x", String),
+            ] {
+                assert_eq!(type_of(&format!("function f({}) {{}}", params), binding), expected, "case {:?}", params);
+            }
+            assert_eq!(type_of("function f(a) { const x = a as number; }", "x"), Float);
+        }
+
+        #[test]
+        fn destructured_bindings() {
+            let source = "function f({ w = 1, h: height, ...rest }, [first], ...others) {}";
+            assert_eq!(type_of(source, "w"), Int);
+            assert_eq!(type_of(source, "height"), Unknown);
+            assert_eq!(type_of(source, "rest"), Object);
+            assert_eq!(type_of(source, "first"), Unknown);
+            assert_eq!(type_of(source, "others"), Object);
+        }
+
+        #[test]
+        fn untyped_bindings_fall_back_to_unknown() {
+            let module = parse_with("function f(a, b) { const c = a; const d = g(b); }", Syntax::Typescript(Default::default()));
+            let function = &first_function(&module).function;
+            let summary = infer_function_types(function.params.iter().map(|param| &param.pat), function.body.as_ref().unwrap());
+            assert_eq!((summary.unknown, summary.total()), (4, 4));
+            assert_eq!(summary.numeric_share(), None);
+        }
+
+        #[test]
+        fn nested_functions_are_inferred_on_their_own() {
+            let module = parse_with("function f(a) { const x = 1; const g = (y) => { const z = 'z'; }; }", Syntax::Typescript(Default::default()));
+            let function = &first_function(&module).function;
+            let summary = infer_function_types(function.params.iter().map(|param| &param.pat), function.body.as_ref().unwrap());
+            // a, x and g only
+            assert_eq!((summary.int, summary.string, summary.total()), (1, 0, 3));
+            assert_eq!(summary.numeric_share(), Some(1.0 / 3.0));
+        }
+    }
+}
@@ -0,0 +1,256 @@
+pub mod typed_arrays {
+    use std::collections::HashSet;
+    use swc_ecma_ast::{
+        ArrowExpr, CallExpr, Callee, DoWhileStmt, Expr, ForInStmt, ForOfStmt, ForStmt, Function, MemberExpr,
+        MemberProp, NewExpr, Pat, TsEntityName, TsType, VarDeclarator, WhileStmt,
+    };
+    use swc_ecma_visit::{Visit, VisitWith};
+    use crate::visitor::type_inference::type_inference::{infer_ts_type, InferredType, TYPED_ARRAY_CONSTRUCTORS};
+
+    const DATA_VIEW_PREFIXES: [&str; 2] = ["get", "set"];
+    const DATA_VIEW_TYPES: [&str; 10] = [
+        "Int8", "Uint8", "Int16", "Uint16", "Int32", "Uint32", "Float32", "Float64", "BigInt64", "BigUint64",
+    ];
+
+    /// How a function works with `ArrayBuffer` backed memory.
+    #[derive(Clone, Copy, Default, Debug)]
+    pub struct TypedArrayUsage {
+        pub constructions: usize,
+        pub loop_accesses: usize,
+        pub image_data_reads: usize,
+        pub data_view_accesses: usize,
+    }
+
+    impl TypedArrayUsage {
+        /// Memory that is indexed in hot loops or comes from a canvas can be shared
+        /// with a WASM module without copying.
+        pub fn is_zero_copy_candidate(&self) -> bool {
+            self.loop_accesses > 0 || self.image_data_reads > 0
+        }
+    }
+
+    pub fn detect_typed_array_usage<'p, B>(params: impl IntoIterator<Item = &'p Pat>, body: &B) -> TypedArrayUsage
+    where
+        B: VisitWith<TypedArrayVisitor>,
+    {
+        let mut visitor = TypedArrayVisitor::default();
+        for param in params {
+            if let Pat::Ident(binding) = param {
+                let annotated = binding.type_ann.as_ref().map(|ann| infer_ts_type(&ann.type_ann));
+                if annotated == Some(InferredType::TypedArray) {
+                    visitor.typed_bindings.insert(binding.id.sym.to_string());
+                }
+                if binding.type_ann.as_ref().is_some_and(|ann| _is_data_view_type(&ann.type_ann)) {
+                    visitor.data_view_bindings.insert(binding.id.sym.to_string());
+                }
+            }
+        }
+        body.visit_with(&mut visitor);
+        visitor.usage
+    }
+
+    #[derive(Default)]
+    pub struct TypedArrayVisitor {
+        usage: TypedArrayUsage,
+        typed_bindings: HashSet<String>,
+        image_data_bindings: HashSet<String>,
+        data_view_bindings: HashSet<String>,
+        loop_depth: usize,
+    }
+
+    impl TypedArrayVisitor {
+        fn is_typed_array(&self, expr: &Expr) -> bool {
+            match expr.unwrap_parens() {
+                Expr::Ident(ident) => self.typed_bindings.contains(ident.sym.as_ref()),
+                Expr::New(new_expr) => _is_typed_array_constructor(&new_expr.callee),
+                Expr::Member(member_expr) => self.is_pixel_data(member_expr),
+                _ => false,
+            }
+        }
+
+        /// A `DataView` binding or `new DataView(...)`
+        fn is_data_view(&self, expr: &Expr) -> bool {
+            match expr.unwrap_parens() {
+                Expr::Ident(ident) => self.data_view_bindings.contains(ident.sym.as_ref()),
+                Expr::New(new_expr) => _is_data_view_constructor(&new_expr.callee),
+                _ => false,
+            }
+        }
+
+        /// `imageData.data` or `ctx.getImageData(...).data`
+        fn is_pixel_data(&self, member_expr: &MemberExpr) -> bool {
+            let is_data_prop = matches!(&member_expr.prop, MemberProp::Ident(prop) if prop.sym.as_ref() == "data");
+            is_data_prop && match member_expr.obj.unwrap_parens() {
+                Expr::Ident(ident) => self.image_data_bindings.contains(ident.sym.as_ref()),
+                Expr::Call(call_expr) => _is_get_image_data(call_expr),
+                _ => false,
+            }
+        }
+
+        fn visit_loop_body<N: VisitWith<Self>>(&mut self, body: &N) {
+            self.loop_depth += 1;
+            body.visit_with(self);
+            self.loop_depth -= 1;
+        }
+    }
+
+    impl Visit for TypedArrayVisitor {
+        // Nested functions are analyzed on their own
+        fn visit_function(&mut self, _: &Function) {}
+        fn visit_arrow_expr(&mut self, _: &ArrowExpr) {}
+
+        fn visit_var_declarator(&mut self, n: &VarDeclarator) {
+            n.init.visit_with(self);
+            if let (Pat::Ident(binding), Some(init)) = (&n.name, &n.init) {
+                let name = binding.id.sym.to_string();
+                let annotated = binding.type_ann.as_ref().map(|ann| infer_ts_type(&ann.type_ann));
+                let is_typed_from_call = match init.unwrap_parens() {
+                    // `Float32Array.from(...)` / `Float32Array.of(...)`
+                    Expr::Call(call_expr) => match &call_expr.callee {
+                        Callee::Expr(callee) => matches!(&**callee, Expr::Member(member) if _is_typed_array_constructor(&member.obj)),
+                        _ => false,
+                    },
+                    _ => false,
+                };
+                if binding.type_ann.as_ref().is_some_and(|ann| _is_data_view_type(&ann.type_ann)) || self.is_data_view(init) {
+                    self.data_view_bindings.insert(name.clone());
+                }
+                if annotated == Some(InferredType::TypedArray) || is_typed_from_call || self.is_typed_array(init) {
+                    self.typed_bindings.insert(name);
+                } else if matches!(init.unwrap_parens(), Expr::Call(call_expr) if _is_get_image_data(call_expr)) {
+                    self.image_data_bindings.insert(name);
+                }
+            }
+        }
+
+        fn visit_new_expr(&mut self, n: &NewExpr) {
+            n.visit_children_with(self);
+            if _is_typed_array_constructor(&n.callee) {
+                self.usage.constructions += 1;
+            }
+        }
+
+        fn visit_call_expr(&mut self, n: &CallExpr) {
+            n.visit_children_with(self);
+            if _is_get_image_data(n) {
+                self.usage.image_data_reads += 1;
+            }
+            if let Callee::Expr(callee) = &n.callee {
+                // Readers and protocol buffers often have methods of the same names
+                if let Expr::Member(MemberExpr { obj, prop: MemberProp::Ident(prop), .. }) = &**callee {
+                    if _is_data_view_method(prop.sym.as_ref()) && self.is_data_view(obj) {
+                        self.usage.data_view_accesses += 1;
+                        if self.loop_depth > 0 {
+                            self.usage.loop_accesses += 1;
+                        }
+                    }
+                }
+            }
+        }
+
+        fn visit_member_expr(&mut self, n: &MemberExpr) {
+            n.visit_children_with(self);
+            if self.loop_depth > 0 && matches!(n.prop, MemberProp::Computed(_)) && self.is_typed_array(&n.obj) {
+                self.usage.loop_accesses += 1;
+            }
+        }
+
+        fn visit_for_stmt(&mut self, n: &ForStmt) {
+            n.init.visit_with(self);
+            n.test.visit_with(self);
+            n.update.visit_with(self);
+            self.visit_loop_body(&n.body);
+        }
+
+        fn visit_for_in_stmt(&mut self, n: &ForInStmt) {
+            n.right.visit_with(self);
+            self.visit_loop_body(&n.body);
+        }
+
+        fn visit_for_of_stmt(&mut self, n: &ForOfStmt) {
+            n.right.visit_with(self);
+            self.visit_loop_body(&n.body);
+        }
+
+        fn visit_while_stmt(&mut self, n: &WhileStmt) {
+            n.test.visit_with(self);
+            self.visit_loop_body(&n.body);
+        }
+
+        fn visit_do_while_stmt(&mut self, n: &DoWhileStmt) {
+            self.visit_loop_body(&n.body);
+            n.test.visit_with(self);
+        }
+    }
+
+    fn _is_typed_array_constructor(callee: &Expr) -> bool {
+        matches!(callee, Expr::Ident(ident) if TYPED_ARRAY_CONSTRUCTORS.contains(&ident.sym.as_ref()))
+    }
+
+    fn _is_data_view_constructor(callee: &Expr) -> bool {
+        matches!(callee, Expr::Ident(ident) if ident.sym.as_ref() == "DataView")
+    }
+
+    /// The `DataView` annotation
+    fn _is_data_view_type(ts_type: &TsType) -> bool {
+        matches!(ts_type, TsType::TsTypeRef(type_ref) if matches!(&type_ref.type_name, TsEntityName::Ident(ident) if ident.sym.as_ref() == "DataView"))
+    }
+
+    fn _is_get_image_data(call_expr: &CallExpr) -> bool {
+        match &call_expr.callee {
+            Callee::Expr(callee) => matches!(
+                &**callee,
+                Expr::Member(MemberExpr { prop: MemberProp::Ident(prop), .. }) if prop.sym.as_ref() == "getImageData"
+            ),
+            _ => false,
+        }
+    }
+
+    /// `view.getFloat32(...)`, `view.setUint8(...)` and friends
+    fn _is_data_view_method(method: &str) -> bool {
+        DATA_VIEW_PREFIXES.iter().any(|prefix| {
+            method.strip_prefix(prefix).is_some_and(|rest| DATA_VIEW_TYPES.contains(&rest))
+        })
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use swc_ecma_parser::Syntax;
+        use crate::fixtures::fixtures::{first_function, parse_with};
+        use super::{detect_typed_array_usage, TypedArrayUsage};
+
+        fn usage_of(source: &str) -> TypedArrayUsage {
+            let module = parse_with(source, Syntax::Typescript(Default::default()));
+            let function = &first_function(&module).function;
+            detect_typed_array_usage(function.params.iter().map(|param| &param.pat), function.body.as_ref().unwrap())
+        }
+
+        #[test]
+        fn data_view_constructed_in_the_function_is_counted() {
+            let usage = usage_of("function f(buffer) { const view = new DataView(buffer); for (;;) { view.getFloat32(0); } }");
+            assert_eq!(usage.data_view_accesses, 1);
+            assert_eq!(usage.loop_accesses, 1);
+            assert!(usage.is_zero_copy_candidate());
+        }
+
+        #[test]
+        fn annotated_data_view_parameter_is_counted() {
+            let usage = usage_of("function f(view: DataView) { view.setUint8(0, 1); }");
+            assert_eq!(usage.data_view_accesses, 1);
+            assert_eq!(usage.loop_accesses, 0);
+        }
+
+        #[test]
+        fn inline_data_view_is_counted() {
+            assert_eq!(usage_of("function f(buffer) { return new DataView(buffer).getInt32(0); }").data_view_accesses, 1);
+        }
+
+        #[test]
+        fn methods_of_the_same_name_on_other_objects_are_not_counted() {
+            let usage = usage_of("function f(reader) { for (;;) { reader.getUint8(); reader.setFloat32(1); } }");
+            assert_eq!(usage.data_view_accesses, 0);
+            assert_eq!(usage.loop_accesses, 0);
+            assert!(!usage.is_zero_copy_candidate());
+        }
+    }
+}
@@ -1,56 +1,121 @@
 
 
 pub mod function_analysis_visitor {
-    use std::rc::Rc;
-    use swc_common::{SourceMap, Spanned};
-    use swc_ecma_ast::{ArrowExpr, BlockStmt, Expr, BlockStmtOrExpr, FnDecl, FnExpr, Stmt};
+    use swc_common::{Span, Spanned};
+    use swc_ecma_ast::{
+        ArrowExpr, AssignExpr, BlockStmt, Expr, BlockStmtOrExpr, FnDecl, FnExpr, KeyValueProp, MemberProp, Pat,
+        PatOrExpr, PropName, Stmt, VarDeclarator,
+    };
     use swc_ecma_visit::{Visit, VisitWith};
-    use crate::Metrics;
+    use crate::visitor::FunctionAnalysis;
+    use crate::visitor::call_graph::call_graph::{collect_callees, CalleeVisitor};
+    use crate::visitor::hotspots::hotspots::{collect_hotspots, HotspotVisitor};
+    use crate::visitor::function_analysis::function_analysis::Recursion;
     use crate::visitor::loop_analysis::loop_analysis;
-    use crate::visitor::cyclomatic_complexity::cyclomatic_complexity;
-    use crate::visitor::scoring::scoring::try_report_function;
+    use crate::visitor::cyclomatic_complexity::cyclomatic_complexity::{self, CyclomaticComplexityVisitor};
+    use crate::visitor::cognitive_complexity::cognitive_complexity::{self, CognitiveComplexityVisitor};
     use crate::visitor::string_counter::string_counter;
+    use crate::visitor::type_inference::type_inference::{infer_function_types, TypeInferenceVisitor};
+    use crate::visitor::typed_arrays::typed_arrays::{detect_typed_array_usage, TypedArrayVisitor};
 
 
-    pub struct FunctionAnalysisVisitor<'a> {
-        pub cyclomatic_complexity: usize,
+    #[derive(Default)]
+    pub struct FunctionAnalysisVisitor {
         pub current_loop_depth: usize,
         pub max_loop_depth: usize,
         pub arithmetic_operations: usize,
         pub string_operations: usize,
-        pub thresholds: &'a Metrics,
-        pub source_map: Rc<SourceMap>,
+        /// Every function of the module, in the order they were visited
+        pub functions: Vec<FunctionAnalysis>,
+        analyzing_function: bool,
+        // Name of the variable the next function expression is assigned to
+        pending_name: Option<String>,
+        // Names of the recorded functions enclosing the visited code
+        enclosing: Vec<String>,
     }
 
-    impl<'a> FunctionAnalysisVisitor<'a> {
-        pub fn new(thresholds: &'a Metrics, source_map: Rc<SourceMap>) -> Self {
+    impl FunctionAnalysisVisitor {
+        pub fn new() -> Self {
             FunctionAnalysisVisitor {
-                cyclomatic_complexity: 1,
                 current_loop_depth: 0,
                 max_loop_depth: 0,
                 arithmetic_operations: 0,
                 string_operations: 0,
-                thresholds,
-                source_map,
+                functions: Vec::new(),
+                analyzing_function: false,
+                pending_name: None,
+                enclosing: Vec::new(),
             }
         }
 
         pub fn reset_metrics(&mut self) {
-            self.cyclomatic_complexity = 1;
             self.current_loop_depth = 0;
             self.max_loop_depth = 0;
             self.arithmetic_operations = 0;
             self.string_operations = 0;
         }
 
+        fn name_function_expression(&mut self, expr: &Expr, name: &str) {
+            if !self.analyzing_function && matches!(expr.unwrap_parens(), Expr::Fn(_) | Expr::Arrow(_)) {
+                self.pending_name = Some(name.to_string());
+            }
+        }
+
+        // Functions nested in a recorded function are qualified with its name
+        fn visit_nested_functions<N: VisitWith<Self>>(&mut self, n: &N, recorded: bool) {
+            if recorded {
+                let name = self.functions.last().map(|function| function.display_name().to_string()).unwrap_or_default();
+                self.enclosing.push(name);
+            }
+            n.visit_children_with(self);
+            if recorded {
+                self.enclosing.pop();
+            }
+        }
+
         fn analyze_function_body(&mut self, body: &BlockStmt) {
             for stmt in &body.stmts {
                 self.analyze_statement(stmt);
             }
         }
 
+        // Whole-function analyses walk the body on their own
+        fn record_function<B>(&mut self, name: Option<String>, span: Span, params: Vec<&Pat>, body: &B)
+        where
+            B: VisitWith<CyclomaticComplexityVisitor>
+                + for<'n> VisitWith<CognitiveComplexityVisitor<'n>>
+                + VisitWith<TypeInferenceVisitor>
+                + VisitWith<TypedArrayVisitor>
+                + VisitWith<CalleeVisitor>
+                + VisitWith<HotspotVisitor>,
+        {
+            let qualified_name = self.enclosing.iter()
+                .map(String::as_str)
+                .chain([name.as_deref().unwrap_or("<anonymous>")])
+                .collect::<Vec<_>>()
+                .join(".");
+            self.functions.push(FunctionAnalysis {
+                span,
+                qualified_name,
+                body_hash: 0,
+                cyclomatic_complexity: cyclomatic_complexity::measure(body),
+                cognitive_complexity: cognitive_complexity::measure(name.as_deref(), body),
+                max_loop_depth: self.max_loop_depth,
+                arithmetic_operations: self.arithmetic_operations,
+                string_operations: self.string_operations,
+                type_summary: infer_function_types(params.iter().copied(), body),
+                typed_array_usage: detect_typed_array_usage(params, body),
+                callees: collect_callees(body),
+                calls: Vec::new(),
+                hotspots: collect_hotspots(body),
+                recursion: Recursion::None,
+                call_depth: 0,
+                caller_loop_depth: 0,
+                name,
+            });
+        }
+
         pub fn analyze_statement(&mut self, stmt: &Stmt) {
-            cyclomatic_complexity::analyze_statement(stmt, &mut self.cyclomatic_complexity);
             string_counter::analyze_statement(self, stmt);
 
             // Handle loops
@@ -68,34 +133,61 @@ pub mod function_analysis_visitor {
         }
     }
 
-    impl<'a> Visit for FunctionAnalysisVisitor<'a> {
+    impl Visit for FunctionAnalysisVisitor {
+        // Nested functions are skipped while their enclosing function is analyzed,
+        // they are recorded on their own when the visitor descends into it afterwards.
+
         fn visit_fn_decl(&mut self, n: &FnDecl) {
-            self.reset_metrics();
+            if self.analyzing_function {
+                return;
+            }
 
             // Analyze the function body
-            if let Some(body) = &n.function.body {
+            let recorded = if let Some(body) = &n.function.body {
+                self.reset_metrics();
+                self.analyzing_function = true;
                 self.analyze_function_body(body);
-            }
+                self.analyzing_function = false;
+                self.record_function(Some(n.ident.sym.to_string()), n.span(), n.function.params.iter().map(|param| &param.pat).collect(), body);
+                true
+            } else {
+                false
+            };
 
-            try_report_function(&self, n.span());
-            n.visit_children_with(self);
+            self.visit_nested_functions(n, recorded);
         }
 
         fn visit_fn_expr(&mut self, n: &FnExpr) {
-            self.reset_metrics();
+            if self.analyzing_function {
+                return;
+            }
 
-            if let Some(body) = &n.function.body {
+            let assigned_name = self.pending_name.take();
+            let recorded = if let Some(body) = &n.function.body {
+                self.reset_metrics();
+                self.analyzing_function = true;
                 for stmt in &body.stmts {
                     self.analyze_statement(stmt); // Analyze each statement
                 }
-            }
+                self.analyzing_function = false;
+                let name = n.ident.as_ref().map(|ident| ident.sym.to_string()).or(assigned_name);
+                self.record_function(name, n.span(), n.function.params.iter().map(|param| &param.pat).collect(), body);
+                true
+            } else {
+                false
+            };
 
-            try_report_function(&self, n.span());
-            n.visit_children_with(self);
+            self.visit_nested_functions(n, recorded);
         }
 
         fn visit_arrow_expr(&mut self, n: &ArrowExpr) {
+            if self.analyzing_function {
+                return;
+            }
+
+            let name = self.pending_name.take();
             self.reset_metrics();
+            self.analyzing_function = true;
 
             // Arrow functions can have a body that is either a block statement or a single expression
             match &*n.body {
@@ -103,14 +195,58 @@ pub mod function_analysis_visitor {
                     for stmt in &block_stmt.stmts {
                         self.analyze_statement(stmt);
                     }
+                    self.analyzing_function = false;
+                    self.record_function(name, n.span(), n.params.iter().collect(), block_stmt);
                 }
                 BlockStmtOrExpr::Expr(expr) => {
                     self.analyze_expression(expr);
+                    self.analyzing_function = false;
+                    self.record_function(name, n.span(), n.params.iter().collect(), expr);
                 }
             }
 
-            try_report_function(&self, n.span());
+            self.visit_nested_functions(n, true);
+        }
+
+        // `const name = function () {}` and `const name = () => {}` take the variable's name
+        fn visit_var_declarator(&mut self, n: &VarDeclarator) {
+            if let (Pat::Ident(binding), Some(init)) = (&n.name, &n.init) {
+                self.name_function_expression(init, binding.id.sym.as_ref());
+            }
+            n.visit_children_with(self);
+        }
+
+        // `{ name: () => {} }`, as in `module.exports = { name: ... }`
+        fn visit_key_value_prop(&mut self, n: &KeyValueProp) {
+            if let PropName::Ident(key) = &n.key {
+                self.name_function_expression(&n.value, key.sym.as_ref());
+            }
+            n.visit_children_with(self);
+        }
+
+        // `name = () => {}` and `exports.name = function () {}`
+        fn visit_assign_expr(&mut self, n: &AssignExpr) {
+            let target = match &n.left {
+                PatOrExpr::Expr(expr) => Some(&**expr),
+                PatOrExpr::Pat(pat) => match &**pat {
+                    Pat::Expr(expr) => Some(&**expr),
+                    Pat::Ident(binding) => {
+                        self.name_function_expression(&n.right, binding.id.sym.as_ref());
+                        None
+                    }
+                    _ => None,
+                },
+            };
+            match target {
+                Some(Expr::Ident(ident)) => self.name_function_expression(&n.right, ident.sym.as_ref()),
+                Some(Expr::Member(member)) => {
+                    if let MemberProp::Ident(prop) = &member.prop {
+                        self.name_function_expression(&n.right, prop.sym.as_ref());
+                    }
+                }
+                _ => {}
+            }
             n.visit_children_with(self);
         }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,247 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+/// Fresh directory holding the given files, with its own user config directory.
+fn project(name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("wasm-grate-cli-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    fs::create_dir_all(&root).expect("Failed to create fixture project");
+    for (path, content) in files {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).expect("Failed to create fixture directory");
+        fs::write(path, content).expect("Failed to write fixture");
+    }
+    fs::canonicalize(root).expect("Failed to create fixture project")
+}
+
+/// Runs the binary in `root`, with the user config kept inside it.
+fn wasm_grate(root: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_wasm-grate"))
+        .args(args)
+        .current_dir(root)
+        .env("XDG_CONFIG_HOME", root.join("xdg"))
+        .env("HOME", root.join("home"))
+        .output()
+        .expect("Failed to run wasm-grate")
+}
+
+/// Runs the binary in `root` with `input` on its standard input.
+fn wasm_grate_with_stdin(root: &Path, args: &[&str], input: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_wasm-grate"))
+        .args(args)
+        .current_dir(root)
+        .env("XDG_CONFIG_HOME", root.join("xdg"))
+        .env("HOME", root.join("home"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to run wasm-grate");
+    // Rejected arguments exit before reading it
+    let _ = child.stdin.take().unwrap().write_all(input.as_bytes());
+    child.wait_with_output().expect("Failed to run wasm-grate")
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).trim_end().to_string()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).to_string()
+}
+
+// Over every threshold once they are all 0, under the defaults
+const NESTED_LOOPS: &str = "function f(a) { for (;;) { for (;;) { a = a * 2 + 1; } } }\n";
+const ZERO_THRESHOLDS: &str = r#"{ "thresholds": {
+    "cyclomatic_complexity": 0, "cognitive_complexity": 0, "loop_depth": 0, "arithmetic_operations": 0, "string_operations": 0
+} }"#;
+
+#[test]
+fn config_set_get_reset_round_trip() {
+    let root = project("config-round-trip", &[]);
+    let default = stdout(&wasm_grate(&root, &["config", "get", "loop_depth"]));
+
+    assert!(wasm_grate(&root, &["config", "set", "loop_depth", "7"]).status.success());
+    assert_eq!(stdout(&wasm_grate(&root, &["config", "get", "loop_depth"])), "7");
+    assert!(root.join("xdg/wasm-grate/config.json").is_file());
+
+    assert!(wasm_grate(&root, &["config", "reset", "loop_depth"]).status.success());
+    assert_eq!(stdout(&wasm_grate(&root, &["config", "get", "loop_depth"])), default);
+    // Nothing left to store
+    assert!(!root.join("xdg/wasm-grate/config.json").exists());
+    let _ = fs::remove_dir_all(root);
+}
+
+#[test]
+fn project_config_overrides_user_config() {
+    let root = project("config-layers", &[(".git/HEAD", ""), ("src/a.js", "")]);
+    let src = root.join("src");
+    assert!(wasm_grate(&src, &["config", "set", "cyclomatic_complexity", "10"]).status.success());
+    assert!(wasm_grate(&src, &["config", "set", "cyclomatic_complexity", "20", "--project"]).status.success());
+    assert!(root.join(".wasm-grate.json").is_file());
+
+    assert_eq!(stdout(&wasm_grate(&src, &["config", "get", "cyclomatic_complexity"])), "20");
+    let list = stdout(&wasm_grate(&src, &["config", "list"]));
+    let expected = format!("cyclomatic_complexity = 20 (project, {})", root.join(".wasm-grate.json").display());
+    assert!(list.lines().any(|line| line == expected), "{}", list);
+
+    assert!(wasm_grate(&src, &["config", "reset", "--project"]).status.success());
+    assert_eq!(stdout(&wasm_grate(&src, &["config", "get", "cyclomatic_complexity"])), "10");
+    let list = stdout(&wasm_grate(&src, &["config", "list"]));
+    assert!(list.lines().any(|line| line.starts_with("cyclomatic_complexity = 10 (user, ")), "{}", list);
+    let _ = fs::remove_dir_all(root);
+}
+
+#[test]
+fn config_rejects_unknown_keys_and_values() {
+    let root = project("config-invalid", &[]);
+    for args in [
+        ["config", "set", "nesting", "3"].as_slice(),
+        &["config", "get", "nesting"],
+        &["config", "reset", "nesting"],
+        &["config", "set", "loop_depth", "-1"],
+        &["config", "set", "loop_depth", "deep"],
+    ] {
+        let output = wasm_grate(&root, args);
+        assert_eq!(output.status.code(), Some(2), "case {:?}: {}", args, stderr(&output));
+    }
+    assert!(stderr(&wasm_grate(&root, &["config", "set", "nesting", "3"])).contains("unknown threshold 'nesting'"));
+    assert!(!root.join("xdg").exists());
+    let _ = fs::remove_dir_all(root);
+}
+
+#[test]
+fn invalid_config_file_is_a_configuration_error() {
+    let root = project("config-invalid-file", &[(".wasm-grate.json", r#"{ "thresholds": { "nesting": 3 } }"#), ("a.js", "")]);
+    let output = wasm_grate(&root, &["-p", "a.js"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains(".wasm-grate.json"), "{}", stderr(&output));
+    let _ = fs::remove_dir_all(root);
+}
+
+#[test]
+fn threshold_flag_overrides_file_config() {
+    let root = project("threshold-flag", &[(".wasm-grate.json", ZERO_THRESHOLDS), ("a.js", NESTED_LOOPS)]);
+    let flagged = stdout(&wasm_grate(&root, &["-p", "a.js"]));
+    assert!(flagged.contains("1 of 1 functions flagged"), "{}", flagged);
+
+    let overridden = stdout(&wasm_grate(&root, &["-p", "a.js", "--threshold", "cyclomatic_complexity=1000"]));
+    assert!(overridden.contains("0 of 1 functions flagged"), "{}", overridden);
+
+    let output = wasm_grate(&root, &["-p", "a.js", "--threshold", "nesting=1"]);
+    assert_eq!(output.status.code(), Some(2), "{}", stderr(&output));
+    let _ = fs::remove_dir_all(root);
+}
+
+#[test]
+fn stdin_filename_picks_the_syntax() {
+    let root = project("stdin-syntax", &[]);
+    let typescript = "function scale(x: number): number { return x * 2; }\n";
+    let output = wasm_grate_with_stdin(&root, &["--stdin", "--stdin-filename", "src/scale.ts"], typescript);
+    assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+
+    // Annotations are not JavaScript
+    for args in [["--stdin", "--stdin-filename", "src/scale.js"].as_slice(), &["--stdin"], &["-p", "-"]] {
+        let output = wasm_grate_with_stdin(&root, args, typescript);
+        assert_eq!(output.status.code(), Some(3), "case {:?}: {}", args, stderr(&output));
+    }
+    let _ = fs::remove_dir_all(root);
+}
+
+#[test]
+fn stdin_filename_names_the_source_in_reports() {
+    let root = project("stdin-paths", &[(".git/HEAD", ""), (".wasm-grate.json", ZERO_THRESHOLDS)]);
+    for (args, file) in [
+        (["--stdin", "--stdin-filename", "src/loops.js", "--format", "ndjson"].as_slice(), "src/loops.js"),
+        (&["--stdin", "--format", "ndjson"], "<stdin>"),
+    ] {
+        let output = wasm_grate_with_stdin(&root, args, NESTED_LOOPS);
+        assert!(output.status.success(), "case {:?}: {}", args, stderr(&output));
+        let record: serde_json::Value = serde_json::from_str(&stdout(&output)).expect("Report is not one JSON line");
+        assert_eq!(record["file"], file, "case {:?}", args);
+        assert_eq!(record["function"], "f");
+    }
+    let _ = fs::remove_dir_all(root);
+}
+
+#[test]
+fn stdin_source_uses_the_config_of_its_directory_outside_a_repository() {
+    let root = project("stdin-config", &[(".wasm-grate.json", ZERO_THRESHOLDS)]);
+    for args in [["--stdin"].as_slice(), &["--stdin", "--stdin-filename", "loops.js"]] {
+        let report = stdout(&wasm_grate_with_stdin(&root, args, NESTED_LOOPS));
+        assert!(report.contains("1 of 1 functions flagged"), "case {:?}: {}", args, report);
+    }
+    let _ = fs::remove_dir_all(root);
+}
+
+#[test]
+fn stdin_is_rejected_with_a_path_or_file_options() {
+    let root = project("stdin-conflicts", &[("a.js", NESTED_LOOPS)]);
+    for args in [
+        ["--stdin", "-p", "a.js"].as_slice(),
+        &["--stdin", "a.js"],
+        &["--stdin", "--watch"],
+        &["--stdin", "--record-history"],
+        &["--stdin", "--changed-since", "HEAD"],
+        &["-p", "a.js", "--stdin-filename", "b.js"],
+    ] {
+        let output = wasm_grate_with_stdin(&root, args, NESTED_LOOPS);
+        assert_eq!(output.status.code(), Some(2), "case {:?}: {}", args, stderr(&output));
+    }
+    let _ = fs::remove_dir_all(root);
+}
+
+#[test]
+fn findings_over_the_limits_exit_with_1() {
+    let root = project("exit-findings", &[(".wasm-grate.json", ZERO_THRESHOLDS), ("a.js", NESTED_LOOPS)]);
+    for (args, expected) in [
+        (["-p", "a.js"].as_slice(), 0),
+        (&["-p", "a.js", "--max-findings", "1"], 0),
+        (&["-p", "a.js", "--max-findings", "0"], 1),
+        (&["-p", "a.js", "--fail-on-score", "10"], 1),
+        (&["-p", "a.js", "--fail-on-score", "10", "--threshold", "cyclomatic_complexity=1000"], 0),
+    ] {
+        let output = wasm_grate(&root, args);
+        assert_eq!(output.status.code(), Some(expected), "case {:?}: {}", args, stderr(&output));
+    }
+    let _ = fs::remove_dir_all(root);
+}
+
+#[test]
+fn configuration_errors_exit_with_2() {
+    let root = project("exit-configuration", &[("a.js", NESTED_LOOPS), ("baseline.json", "not json")]);
+    for args in [
+        ["-p", "missing.js"].as_slice(),
+        &["-p", "a.js", "--baseline", "baseline.json"],
+        &["-p", "a.js", "--baseline", "missing.json"],
+        &["-p", "a.js", "--all"],
+        &["-p", "a.js", "--fail-on-score", "11"],
+        &["-p", "a.js", "--top-percent", "101"],
+        &["-p", "a.js", "--format", "yaml"],
+        &["-p", "a.js", "--output", "missing/report.txt"],
+        &["--unknown-flag"],
+    ] {
+        let output = wasm_grate(&root, args);
+        assert_eq!(output.status.code(), Some(2), "case {:?}: {}", args, stderr(&output));
+    }
+    let _ = fs::remove_dir_all(root);
+}
+
+#[test]
+fn parse_errors_exit_with_3_even_over_the_limits() {
+    let root = project("exit-parse", &[(".wasm-grate.json", ZERO_THRESHOLDS), ("a.js", NESTED_LOOPS), ("broken.js", "function (")]);
+    for args in [
+        ["-p", "."].as_slice(),
+        &["-p", "broken.js"],
+        // The findings of a.js are over both limits
+        &["-p", ".", "--max-findings", "0", "--fail-on-score", "1"],
+    ] {
+        let output = wasm_grate(&root, args);
+        assert_eq!(output.status.code(), Some(3), "case {:?}: {}", args, stderr(&output));
+    }
+    // The partial report is still written
+    assert!(stdout(&wasm_grate(&root, &["-p", "."])).contains("1 of 1 functions flagged"));
+    let _ = fs::remove_dir_all(root);
+}